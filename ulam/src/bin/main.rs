@@ -1,4 +1,6 @@
 use clap::Parser;
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
 use plotters::coord::types::RangedCoordf64;
 use plotters::drawing::IntoDrawingArea;
 use plotters::prelude::BitMapBackend;
@@ -12,22 +14,73 @@ use plotters::style::IntoFont;
 use std::fs::create_dir;
 use std::fs::create_dir_all;
 use std::path::Path;
+use ulam::ulam::generator::collatz::CollatzGenerator;
+use ulam::ulam::generator::combinators::is_combination;
+use ulam::ulam::generator::combinators::parse_combination;
+use ulam::ulam::generator::constellations::ConstellationGenerator;
+use ulam::ulam::generator::digits::DigitsGenerator;
+use ulam::ulam::generator::digits::Property;
+use ulam::ulam::generator::divisors::DivisorsGenerator;
+use ulam::ulam::generator::expression::ExpressionGenerator;
+use ulam::ulam::generator::file::FileGenerator;
 use ulam::ulam::generator::generator::Generator;
-use ulam::ulam::generator::prime1s::Prime1sGenerator;
-use ulam::ulam::generator::prime3s::Prime3sGenerator;
-use ulam::ulam::generator::prime7s::Prime7sGenerator;
+use ulam::ulam::generator::multiplicative::MultiplicativeGenerator;
+use ulam::ulam::generator::multiplicative::Structure;
+use ulam::ulam::generator::number::Number;
+use ulam::ulam::generator::polygonal::Figurate;
+use ulam::ulam::generator::polygonal::PolygonalGenerator;
+use ulam::ulam::generator::polynomial::PolynomialGenerator;
+use ulam::ulam::generator::prime_factors::PrimeFactorsGenerator;
+use ulam::ulam::generator::prime_gaps::PrimeGapsGenerator;
 use ulam::ulam::generator::primes::PrimesGenerator;
+use ulam::ulam::generator::primes_in_class::PrimesInClassGenerator;
+use ulam::ulam::generator::pseudoprimes::PseudoprimeGenerator;
+use ulam::ulam::generator::pseudoprimes::Test;
+use ulam::ulam::generator::random::random_seed;
+use ulam::ulam::generator::random::BernoulliGenerator;
+use ulam::ulam::generator::random::CramerGenerator;
+use ulam::ulam::generator::random::ShuffledGenerator;
+use ulam::ulam::generator::residues::ResiduesGenerator;
+use ulam::ulam::generator::sequences::Sequence;
+use ulam::ulam::generator::sequences::SequenceGenerator;
+use ulam::ulam::generator::special_primes::Family;
+use ulam::ulam::generator::special_primes::SpecialPrimesGenerator;
 use ulam::ulam::generator::squares::SquareGenerator;
 use ulam::ulam::generator::times::TimesGenerator;
+use ulam::ulam::generator::totient::TotientGenerator;
+use ulam::ulam::tile::gosper_curve::GosperCurve;
+use ulam::ulam::tile::grid;
+use ulam::ulam::tile::grid::Grid;
 use ulam::ulam::tile::hexagon_spiral::HexagonSpiral;
+use ulam::ulam::tile::klauber_triangle::KlauberTriangle;
+use ulam::ulam::tile::lattice::Lattice;
+use ulam::ulam::tile::lattice::Ring;
+use ulam::ulam::tile::sacks_spiral::SacksSpiral;
+use ulam::ulam::tile::square_curve::Curve;
+use ulam::ulam::tile::square_curve::SquareCurve;
 use ulam::ulam::tile::square_spiral::SquareSpiral;
 use ulam::ulam::tile::square_zigzag::SquareZigzag;
 use ulam::ulam::tile::tile::Tile;
 use ulam::ulam::tile::tile::MARGIN;
+use ulam::ulam::tile::triangle_spiral::TriangleSpiral;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
   let arg: AppArg = AppArg::parse();
 
+  match arg.number.as_str() {
+    "usize" => draw(&arg, generator(&arg, create_generator)?),
+    "u64" => draw(&arg, generator(&arg, create_number_generator::<u64>)?),
+    "u128" => draw(&arg, generator(&arg, create_number_generator::<u128>)?),
+    #[cfg(feature = "bigint")]
+    "big" => draw(&arg, generator(&arg, create_number_generator::<BigUint>)?),
+    _ => panic!(),
+  }
+}
+
+fn draw<N: Number>(
+  arg: &AppArg,
+  gen: Option<Box<dyn Generator<N>>>,
+) -> Result<(), Box<dyn std::error::Error>> {
   let style = TextStyle::from(("sans-serif", 30).into_font()).color(&BLACK);
 
   if !Path::new("output").is_dir() {
@@ -49,58 +102,118 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
   if let Some(ref parent) = path.parent() {
     if !parent.is_dir() {
       println!("{:?}", parent);
-      create_dir_all(parent).expect(&format!("Can't create dir({:?})", parent));
+      create_dir_all(parent).unwrap_or_else(|_| panic!("Can't create dir({:?})", parent));
     }
   }
 
-  let root = BitMapBackend::new(&file_path, (arg.image_size, arg.image_size)).into_drawing_area();
+  let height = image_height(arg, gen.as_ref().map_or(0, |gen| gen.data_num()))?;
+  let root = BitMapBackend::new(&file_path, (arg.image_size, height)).into_drawing_area();
   root.fill(&WHITE)?;
 
   let (upper, lower) = root.split_vertically(MARGIN as u32);
+  // The bottom margin of the chart, kept for the legend or colour bar.
+  let (_, footer) = root.split_vertically(height - MARGIN as u32 / 2);
 
-  let size = ((arg.image_size - MARGIN as u32) / 2) as f64;
+  let (size, size_y) = chart_size(arg.image_size, height);
   let chart = ChartBuilder::on(&lower)
     .margin_left(MARGIN as f64 * 0.75)
     .margin_right(MARGIN as f64 * 0.75)
     .margin_bottom(MARGIN as u32 / 2)
-    .build_cartesian_2d(-size..size, -size..size)?;
+    .build_cartesian_2d(-size..size, -size_y..size_y)?;
   let plotting_area = chart.plotting_area();
 
-  let gen = create_generator(&arg)?;
-  upper.draw_text(&format!("{}", gen.generator_info()), &style, (20, 10))?;
-
-  let mut tile = create_tile(&arg, gen, &plotting_area)?;
-  upper.draw_text(&format!("{}", tile.tile_info()), &style, (20, 50))?;
+  let mut tile = create_tile(arg, gen, plotting_area)?;
+  upper.draw_text(&tile.tile_info(), &style, (20, 50))?;
+  tile.draw_legend(&footer)?;
+  tile.draw_color_bar(&footer)?;
 
   while let Some(result) = tile.draw_next() {
     result?;
   }
 
+  // Drawn last, as some generators count what they yield.
+  upper.draw_text(&tile.generator_info(), &style, (20, 10))?;
+
   root
     .present()
-    .expect(&format!("Failed to output file({})", file_path));
+    .unwrap_or_else(|_| panic!("Failed to output file({})", file_path));
 
   Ok(())
 }
 
+// Height of the image, which only a grid makes taller than it is wide.
+fn image_height(arg: &AppArg, data_num: usize) -> Result<u32, Box<dyn std::error::Error>> {
+  if arg.tile == "grid" {
+    grid::image_height(&arg.tp, data_num, arg.image_size)
+  } else {
+    Ok(arg.image_size)
+  }
+}
+
+// Half the width and height of the chart, with one scale on both axes.
+fn chart_size(image_size: u32, height: u32) -> (f64, f64) {
+  let size = ((image_size - MARGIN as u32) / 2) as f64;
+  let plot = |side: u32| (side - MARGIN as u32 * 3 / 2) as f64;
+  (size, size * plot(height) / plot(image_size))
+}
+
+type BoxedGenerator<N> = Box<dyn Generator<N>>;
+type GeneratorResult<N> = Result<BoxedGenerator<N>, Box<dyn std::error::Error>>;
+
+// Builds the generator, except for the lattice tiles, which walk the range
+// of --gp and take none.
+fn generator<N: Number + 'static>(
+  arg: &AppArg,
+  create: fn(&AppArg) -> GeneratorResult<N>,
+) -> Result<Option<BoxedGenerator<N>>, Box<dyn std::error::Error>> {
+  if Ring::from_name(&arg.tile).is_some() {
+    return Ok(None);
+  }
+
+  Ok(Some(shuffled(arg, create(arg)?)))
+}
+
+// Wraps the generator in a same-density shuffle for --shuffle.
+fn shuffled<N: Number + 'static>(
+  arg: &AppArg,
+  gen: Box<dyn Generator<N>>,
+) -> Box<dyn Generator<N>> {
+  if !arg.shuffle {
+    return gen;
+  }
+
+  Box::new(ShuffledGenerator::new(gen, arg.seed))
+}
+
 fn create_generator(arg: &AppArg) -> Result<Box<dyn Generator>, Box<dyn std::error::Error>> {
+  if is_combination(&arg.generator) {
+    return parse_combination(&arg.generator, &mut |leaf| {
+      create_generator(&leaf_arg(arg, leaf)?)
+    });
+  }
+
   if arg.generator == "primes" {
+    if let Some(modulus) = arg.modulus {
+      let gen = PrimesInClassGenerator::from_gp(&arg.gp, modulus, arg.residues.as_deref())?;
+      return Ok(Box::new(gen));
+    }
+
     let gen = PrimesGenerator::from_gp(&arg.gp)?;
     return Ok(Box::new(gen));
   }
 
   if arg.generator == "prime1s" {
-    let gen = Prime1sGenerator::from_gp(&arg.gp)?;
+    let gen = PrimesInClassGenerator::from_gp(&arg.gp, 10, Some("1"))?;
     return Ok(Box::new(gen));
   }
 
   if arg.generator == "prime3s" {
-    let gen = Prime3sGenerator::from_gp(&arg.gp)?;
+    let gen = PrimesInClassGenerator::from_gp(&arg.gp, 10, Some("3"))?;
     return Ok(Box::new(gen));
   }
 
   if arg.generator == "prime7s" {
-    let gen = Prime7sGenerator::from_gp(&arg.gp)?;
+    let gen = PrimesInClassGenerator::from_gp(&arg.gp, 10, Some("7"))?;
     return Ok(Box::new(gen));
   }
 
@@ -114,14 +227,234 @@ fn create_generator(arg: &AppArg) -> Result<Box<dyn Generator>, Box<dyn std::err
     return Ok(Box::new(gen));
   }
 
+  if arg.generator == "factors" {
+    let gen = PrimeFactorsGenerator::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "residues" {
+    let gen = ResiduesGenerator::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "divisors" {
+    let gen = DivisorsGenerator::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "totient" {
+    let gen = TotientGenerator::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "bigomega" {
+    let gen = PrimeFactorsGenerator::from_gp(&arg.gp)?.with_values();
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "collatz" {
+    let gen = CollatzGenerator::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "gaps" {
+    let gen = PrimeGapsGenerator::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "poly" {
+    let gen =
+      PolynomialGenerator::from_gp(&arg.gp, arg.poly.as_deref().ok_or("--poly is required")?)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "expr" {
+    let gen =
+      ExpressionGenerator::from_gp(&arg.gp, arg.expr.as_deref().ok_or("--expr is required")?)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "file" {
+    let gen = FileGenerator::from_gp(&arg.gp, arg.file.as_deref().ok_or("--file is required")?)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "twins" {
+    let gen = ConstellationGenerator::from_gp(&arg.gp, "0,2")?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "cousins" {
+    let gen = ConstellationGenerator::from_gp(&arg.gp, "0,4")?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "sexy" {
+    let gen = ConstellationGenerator::from_gp(&arg.gp, "0,6")?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "constellations" {
+    let gen =
+      ConstellationGenerator::from_gp(&arg.gp, arg.pattern.as_deref().unwrap_or("0,2;0,4;0,6"))?;
+    return Ok(Box::new(gen));
+  }
+
+  if let Some(family) = Family::from_name(&arg.generator) {
+    let gen = SpecialPrimesGenerator::from_gp(&arg.gp, family)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "polygonal" {
+    let gen = PolygonalGenerator::from_gp(&arg.gp, Figurate::Polygonal)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "triangular" {
+    let gen = PolygonalGenerator::fixed_from_gp(&arg.gp, Figurate::Polygonal(3))?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "pentagonal" {
+    let gen = PolygonalGenerator::fixed_from_gp(&arg.gp, Figurate::Polygonal(5))?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "hexagonal" {
+    let gen = PolygonalGenerator::fixed_from_gp(&arg.gp, Figurate::Polygonal(6))?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "centered" {
+    let gen = PolygonalGenerator::from_gp(&arg.gp, Figurate::Centered)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "pronic" {
+    let gen = PolygonalGenerator::fixed_from_gp(&arg.gp, Figurate::Pronic)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "squarefree" {
+    let gen = MultiplicativeGenerator::fixed_from_gp(&arg.gp, Structure::Squarefree)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "mobius" {
+    let gen = MultiplicativeGenerator::fixed_from_gp(&arg.gp, Structure::Mobius)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "semiprimes" {
+    let gen = MultiplicativeGenerator::fixed_from_gp(&arg.gp, Structure::AlmostPrime(2))?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "almost-primes" {
+    let gen = MultiplicativeGenerator::from_gp(&arg.gp, Structure::AlmostPrime)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "smooth" {
+    let gen = MultiplicativeGenerator::from_gp(&arg.gp, Structure::Smooth)?;
+    return Ok(Box::new(gen));
+  }
+
+  if let Some(test) = Test::from_name(&arg.generator) {
+    let gen = PseudoprimeGenerator::from_gp(&arg.gp, test, &arg.bases)?;
+    if arg.show_primes {
+      return Ok(Box::new(gen.with_primes()));
+    }
+    return Ok(Box::new(gen));
+  }
+
+  if let Some(property) = Property::from_name(&arg.generator) {
+    let gen = DigitsGenerator::from_gp(&arg.gp, property)?;
+    return Ok(Box::new(gen));
+  }
+
+  if let Some(sequence) = Sequence::from_name(&arg.generator) {
+    let gen = SequenceGenerator::from_gp(&arg.gp, sequence)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "cramer" {
+    let gen = CramerGenerator::from_gp(&arg.gp, arg.seed)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "bernoulli" {
+    let gen = BernoulliGenerator::from_gp(&arg.gp, arg.seed)?;
+    return Ok(Box::new(gen));
+  }
+
   panic!()
 }
 
-fn create_tile<'a>(
+fn create_number_generator<N: Number + 'static>(
   arg: &AppArg,
-  gen: Box<dyn Generator>,
+) -> Result<Box<dyn Generator<N>>, Box<dyn std::error::Error>> {
+  if is_combination(&arg.generator) {
+    return parse_combination(&arg.generator, &mut |leaf| {
+      create_number_generator(&leaf_arg(arg, leaf)?)
+    });
+  }
+
+  if arg.generator == "primes" {
+    if let Some(modulus) = arg.modulus {
+      let gen = PrimesInClassGenerator::<N>::from_gp(&arg.gp, modulus, arg.residues.as_deref())?;
+      return Ok(Box::new(gen));
+    }
+
+    let gen = PrimesGenerator::<N>::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "times" {
+    let gen = TimesGenerator::<N>::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.generator == "squares" {
+    let gen = SquareGenerator::<N>::from_gp(&arg.gp)?;
+    return Ok(Box::new(gen));
+  }
+
+  Err(format!("{} doesn't support --number={}", arg.generator, arg.number).into())
+}
+
+// Arguments for one source of a combination. A source such as `times:3`
+// takes the range of --gp followed by its own parameters.
+fn leaf_arg(arg: &AppArg, leaf: &str) -> Result<AppArg, Box<dyn std::error::Error>> {
+  let (generator, params) = leaf.split_once(":").unwrap_or((leaf, ""));
+  let range: Vec<&str> = arg.gp.split(":").take(2).collect();
+  let gp = if params.is_empty() {
+    range.join(":")
+  } else if range.len() == 2 {
+    format!("{}:{}", range.join(":"), params)
+  } else {
+    return Err(format!("{} needs --gp=from:to", leaf).into());
+  };
+
+  Ok(AppArg {
+    generator: generator.to_string(),
+    gp,
+    ..arg.clone()
+  })
+}
+
+fn create_tile<'a, N: Number + 'a>(
+  arg: &AppArg,
+  gen: Option<Box<dyn Generator<N>>>,
   plotting_area: &'a DrawingArea<BitMapBackend, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-) -> Result<Box<dyn Tile + 'a>, Box<dyn std::error::Error>> {
+) -> Result<Box<dyn Tile<N> + 'a>, Box<dyn std::error::Error>> {
+  if let Some(ring) = Ring::from_name(&arg.tile) {
+    let gen = Lattice::from_tp(&arg.tp, &arg.gp, plotting_area, ring)?;
+    return Ok(Box::new(gen));
+  }
+
+  let gen = gen.expect("Only the lattice tiles take no generator");
+
   if arg.tile == "spiral4" {
     let gen = SquareSpiral::from_tp(&arg.tp, gen, plotting_area)?;
     return Ok(Box::new(gen));
@@ -132,6 +465,36 @@ fn create_tile<'a>(
     return Ok(Box::new(gen));
   }
 
+  if arg.tile == "sacks" {
+    let gen = SacksSpiral::from_tp(&arg.tp, gen, plotting_area)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.tile == "spiral3" {
+    let gen = TriangleSpiral::from_tp(&arg.tp, gen, plotting_area)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.tile == "klauber" {
+    let gen = KlauberTriangle::from_tp(&arg.tp, gen, plotting_area)?;
+    return Ok(Box::new(gen));
+  }
+
+  if let Some(curve) = Curve::from_name(&arg.tile) {
+    let gen = SquareCurve::from_tp(&arg.tp, gen, plotting_area, curve)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.tile == "gosper" {
+    let gen = GosperCurve::from_tp(&arg.tp, gen, plotting_area)?;
+    return Ok(Box::new(gen));
+  }
+
+  if arg.tile == "grid" {
+    let gen = Grid::from_tp(&arg.tp, gen, plotting_area)?;
+    return Ok(Box::new(gen));
+  }
+
   if arg.tile == "zigzag4" {
     let gen = SquareZigzag::from_tp(&arg.tp, gen, plotting_area)?;
     return Ok(Box::new(gen));
@@ -140,14 +503,13 @@ fn create_tile<'a>(
   panic!()
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[clap(
   name = "Gen-Tile",
   author = "cuboktahedron",
   version = "v0.1.0",
   about = "Draw with generator and tile."
 )]
-
 struct AppArg {
   #[clap(short, long, default_value = "primes")]
   generator: String,
@@ -166,4 +528,48 @@ struct AppArg {
 
   #[clap(short, long, default_value = "800")]
   image_size: u32,
+
+  /// usize, u64, u128 or big (with the bigint feature)
+  #[clap(long, default_value = "usize")]
+  number: String,
+
+  /// Restricts primes to residue classes modulo this value
+  #[clap(long = "mod")]
+  modulus: Option<usize>,
+
+  /// Comma separated residues for --mod, all coprime residues by default
+  #[clap(long)]
+  residues: Option<String>,
+
+  /// Integer polynomial in n for the poly generator, such as "n^2+n+41"
+  #[clap(long)]
+  poly: Option<String>,
+
+  /// Predicate on n for the expr generator, such as "is_prime(n) && n % 4 == 1"
+  #[clap(long)]
+  expr: Option<String>,
+
+  /// Number list for the file generator, such as an OEIS b-file, or - for stdin
+  #[clap(long)]
+  file: Option<String>,
+
+  /// Offsets of prime constellations, such as "0,2,6", several separated by ";"
+  #[clap(long)]
+  pattern: Option<String>,
+
+  /// Comma separated bases for the pseudoprime generators
+  #[clap(long, default_value = "2")]
+  bases: String,
+
+  /// Draws primes too, apart from the pseudoprimes
+  #[clap(long)]
+  show_primes: bool,
+
+  /// Seed of the random generators and --shuffle, from the clock by default
+  #[clap(long, default_value_t = random_seed(), hide_default_value = true)]
+  seed: u64,
+
+  /// Shuffles the marks of the generator over its window
+  #[clap(long)]
+  shuffle: bool,
 }
@@ -14,11 +14,19 @@ use std::fs::create_dir_all;
 use std::path::Path;
 use ulam::ulam::generator::generator::Generator;
 use ulam::ulam::generator::times::TimesGenerator;
+use ulam::ulam::tile::gosper_curve::GosperCurve;
+use ulam::ulam::tile::grid;
+use ulam::ulam::tile::grid::Grid;
 use ulam::ulam::tile::hexagon_spiral::HexagonSpiral;
+use ulam::ulam::tile::klauber_triangle::KlauberTriangle;
+use ulam::ulam::tile::sacks_spiral::SacksSpiral;
+use ulam::ulam::tile::square_curve::Curve;
+use ulam::ulam::tile::square_curve::SquareCurve;
 use ulam::ulam::tile::square_spiral::SquareSpiral;
 use ulam::ulam::tile::square_zigzag::SquareZigzag;
 use ulam::ulam::tile::tile::Tile;
 use ulam::ulam::tile::tile::MARGIN;
+use ulam::ulam::tile::triangle_spiral::TriangleSpiral;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let arg: AppArg = AppArg::parse();
@@ -37,22 +45,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if let Some(ref parent) = path.parent() {
         if !parent.is_dir() {
             println!("{:?}", parent);
-            create_dir_all(parent).expect(&format!("Can't create dir({:?})", parent));
+            create_dir_all(parent).unwrap_or_else(|_| panic!("Can't create dir({:?})", parent));
         }
     }
 
-    let root = BitMapBackend::gif(&file_path, (arg.image_size, arg.image_size), arg.interval)?
-        .into_drawing_area();
+    // Only a grid makes the image taller than it is wide.
+    let height = if arg.tile == "grid" {
+        grid::image_height(&arg.tp, (arg.to - arg.from) as usize, arg.image_size)?
+    } else {
+        arg.image_size
+    };
+    let root =
+        BitMapBackend::gif(&file_path, (arg.image_size, height), arg.interval)?.into_drawing_area();
     root.fill(&WHITE)?;
 
     let (upper, lower) = root.split_vertically(MARGIN as i32);
 
+    // Half the width and height of the chart, with one scale on both axes.
     let size = ((arg.image_size - MARGIN as u32) / 2) as f64;
+    let plot = |side: u32| (side - MARGIN as u32 * 3 / 2) as f64;
+    let size_y = size * plot(height) / plot(arg.image_size);
     let chart = ChartBuilder::on(&lower)
         .margin_left(MARGIN as f64 * 0.75)
         .margin_right(MARGIN as f64 * 0.75)
         .margin_bottom(MARGIN as u32 / 2)
-        .build_cartesian_2d(-size..size, -size..size)?;
+        .build_cartesian_2d(-size..size, -size_y..size_y)?;
     let plotting_area = chart.plotting_area();
 
     let style = TextStyle::from(("sans-serif", 30).into_font()).color(&BLACK);
@@ -63,27 +80,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let gp = format!("{}:{}:{}", arg.from, arg.to, i);
 
         let gen = Box::new(TimesGenerator::from_gp(&gp)?);
-        let generator_info = gen.generator_info();
-        let mut tile = create_tile(&arg, gen, &plotting_area)?;
+        let mut tile = create_tile(&arg, gen, plotting_area)?;
         let tile_info = tile.tile_info();
         while let Some(result) = tile.draw_next() {
             result?;
         }
 
         upper.fill(&WHITE)?;
-        upper.draw_text(&generator_info, &style, (20, 10))?;
+        upper.draw_text(&tile.generator_info(), &style, (20, 10))?;
         upper.draw_text(&tile_info, &style, (20, 40))?;
         root.present()
-            .expect(&format!("Failed to output file({})", file_path));
+            .unwrap_or_else(|_| panic!("Failed to output file({})", file_path));
 
         println!("times {} done", i);
     }
 
-    let wait_num = (arg.wait_after + arg.interval - 1) / arg.interval;
+    let wait_num = arg.wait_after.div_ceil(arg.interval);
 
     for _ in 0..wait_num {
         root.present()
-            .expect(&format!("Failed to output file({})", file_path));
+            .unwrap_or_else(|_| panic!("Failed to output file({})", file_path));
     }
 
     Ok(())
@@ -104,6 +120,36 @@ fn create_tile<'a>(
         return Ok(Box::new(gen));
     }
 
+    if arg.tile == "sacks" {
+        let gen = SacksSpiral::from_tp(&arg.tp, gen, plotting_area)?;
+        return Ok(Box::new(gen));
+    }
+
+    if arg.tile == "spiral3" {
+        let gen = TriangleSpiral::from_tp(&arg.tp, gen, plotting_area)?;
+        return Ok(Box::new(gen));
+    }
+
+    if arg.tile == "klauber" {
+        let gen = KlauberTriangle::from_tp(&arg.tp, gen, plotting_area)?;
+        return Ok(Box::new(gen));
+    }
+
+    if let Some(curve) = Curve::from_name(&arg.tile) {
+        let gen = SquareCurve::from_tp(&arg.tp, gen, plotting_area, curve)?;
+        return Ok(Box::new(gen));
+    }
+
+    if arg.tile == "gosper" {
+        let gen = GosperCurve::from_tp(&arg.tp, gen, plotting_area)?;
+        return Ok(Box::new(gen));
+    }
+
+    if arg.tile == "grid" {
+        let gen = Grid::from_tp(&arg.tp, gen, plotting_area)?;
+        return Ok(Box::new(gen));
+    }
+
     if arg.tile == "zigzag4" {
         let gen = SquareZigzag::from_tp(&arg.tp, gen, plotting_area)?;
         return Ok(Box::new(gen));
@@ -119,7 +165,6 @@ fn create_tile<'a>(
     version = "v0.1.0",
     about = "Draw with generator and tile."
 )]
-
 struct AppArg {
     #[clap(short, long, default_value = "spiral4")]
     tile: String,
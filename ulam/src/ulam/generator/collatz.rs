@@ -0,0 +1,149 @@
+use crate::ulam::generator::generator::scan_value_range;
+use crate::ulam::generator::generator::Generator;
+
+/// Yields the Collatz stopping time of n, the number of steps n takes to
+/// reach 1, as the value of n. With a step limit k, marks n that reach 1
+/// within k steps instead.
+pub struct CollatzGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  within: Option<usize>,
+}
+
+impl CollatzGenerator {
+  pub fn new(n: usize, skip: usize) -> Self {
+    CollatzGenerator {
+      i: skip,
+      max: n,
+      skip,
+      within: None,
+    }
+  }
+
+  pub fn with_within(mut self, within: usize) -> Self {
+    self.within = Some(within);
+    self
+  }
+
+  /// `gp` is `from:to:k`, with the step limit k optional.
+  pub fn from_gp(gp: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+    let within = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    let gen = Self::new(to, from);
+    if let Some(within) = within {
+      return Ok(gen.with_within(within.parse()?));
+    }
+
+    Ok(gen)
+  }
+}
+
+fn stopping_time(n: usize) -> usize {
+  // Trajectories climb far above n, so step in u128.
+  let mut n = n as u128;
+  let mut steps = 0;
+  while n != 1 {
+    n = if n.is_multiple_of(2) {
+      n / 2
+    } else {
+      3 * n + 1
+    };
+    steps += 1;
+  }
+
+  steps
+}
+
+impl Generator for CollatzGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let within = self.within;
+    self.next_value().map(|(n, v)| match within {
+      Some(k) => (n, v.is_some_and(|v| v <= k as f64)),
+      None => (n, v.is_some()),
+    })
+  }
+
+  fn value_range(&self) -> Option<(f64, f64)> {
+    if self.within.is_some() {
+      return None;
+    }
+
+    scan_value_range(&mut Self::new(self.max, self.skip))
+  }
+
+  fn next_value(&mut self) -> Option<(usize, Option<f64>)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    if i == 0 {
+      return Some((i, None));
+    }
+
+    Some((i, Some(stopping_time(i) as f64)))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    let within = match self.within {
+      Some(k) => format!(", within = {}", k),
+      None => String::new(),
+    };
+    format!(
+      "generator = collatz, from = {}, to = {}{}",
+      self.skip, self.max, within
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let mut ite = CollatzGenerator::new(9, 0);
+    let values: Vec<_> = std::iter::from_fn(|| ite.next_value())
+      .map(|(_, v)| v)
+      .collect();
+    assert_eq!(values[0], None);
+    assert_eq!(
+      values[1..],
+      [0.0, 1.0, 7.0, 2.0, 5.0, 8.0, 16.0, 3.0, 19.0].map(Some)
+    );
+    assert_eq!(stopping_time(27), 111);
+    assert_eq!(ite.value_range(), Some((0.0, 19.0)));
+  }
+
+  #[test]
+  fn test_within() {
+    let mut ite = CollatzGenerator::from_gp("0:9:5").unwrap();
+    let marked: Vec<_> = std::iter::from_fn(|| ite.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect();
+    assert_eq!(marked, vec![1, 2, 4, 5, 8]);
+    assert_eq!(ite.value_range(), None);
+    assert_eq!(
+      ite.generator_info(),
+      "generator = collatz, from = 0, to = 9, within = 5"
+    );
+  }
+}
@@ -0,0 +1,348 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Operator {
+  And,
+  Or,
+  Xor,
+}
+
+impl Operator {
+  fn apply(&self, a: bool, b: bool) -> bool {
+    match self {
+      Operator::And => a && b,
+      Operator::Or => a || b,
+      Operator::Xor => a != b,
+    }
+  }
+
+  fn symbol(&self) -> &'static str {
+    match self {
+      Operator::And => "&",
+      Operator::Or => "|",
+      Operator::Xor => "^",
+    }
+  }
+}
+
+/// Combines two or more sources with `and`, `or` or `xor`. The sources must
+/// yield the same n at every step.
+pub struct CombinedGenerator<N = usize> {
+  operator: Operator,
+  sources: Vec<Box<dyn Generator<N>>>,
+  // The first result of each source, read to check where they start.
+  heads: Vec<Option<(N, bool)>>,
+  label: Option<String>,
+}
+
+impl<N: Number> CombinedGenerator<N> {
+  pub fn new(
+    operator: Operator,
+    mut sources: Vec<Box<dyn Generator<N>>>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    if sources.len() < 2 {
+      return Err("a combinator needs at least two sources".into());
+    }
+
+    let data_num = sources[0].data_num();
+    if let Some(source) = sources.iter().find(|s| s.data_num() != data_num) {
+      return Err(
+        format!(
+          "sources are not aligned({} and {})",
+          sources[0].generator_info(),
+          source.generator_info()
+        )
+        .into(),
+      );
+    }
+
+    let heads: Vec<_> = sources.iter_mut().map(|s| s.next()).collect();
+    let start = |head: &Option<(N, bool)>| head.as_ref().map(|(n, _)| n.clone());
+    if let Some(i) = (1..heads.len()).find(|&i| start(&heads[i]) != start(&heads[0])) {
+      return Err(
+        format!(
+          "sources start at different n({} and {})",
+          sources[0].generator_info(),
+          sources[i].generator_info()
+        )
+        .into(),
+      );
+    }
+
+    Ok(CombinedGenerator {
+      operator,
+      sources,
+      heads,
+      label: None,
+    })
+  }
+
+  /// Replaces the description built from the sources in `generator_info`.
+  pub fn with_label(mut self, label: &str) -> Self {
+    self.label = Some(label.to_string());
+    self
+  }
+}
+
+impl<N: Number> Generator<N> for CombinedGenerator<N> {
+  fn data_num(&self) -> usize {
+    self.sources[0].data_num()
+  }
+
+  fn next(&mut self) -> std::option::Option<(N, bool)> {
+    let results = if self.heads.is_empty() {
+      self.sources.iter_mut().map(|s| s.next()).collect()
+    } else {
+      std::mem::take(&mut self.heads)
+    };
+
+    // Sources that start together and are as long stay aligned; should one
+    // fall out of step anyway, the combination ends there.
+    let mut results = results.into_iter();
+    let (n, mut b) = results.next().flatten()?;
+    for result in results {
+      match result {
+        Some((m, c)) if m == n => b = self.operator.apply(b, c),
+        _ => return None,
+      }
+    }
+
+    Some((n, b))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    if let Some(label) = &self.label {
+      return format!("generator = {}", label);
+    }
+
+    let infos: Vec<String> = self
+      .sources
+      .iter()
+      .map(|s| format!("({})", s.generator_info()))
+      .collect();
+    infos.join(&format!(" {} ", self.operator.symbol()))
+  }
+}
+
+/// Negates a source.
+pub struct NotGenerator<N = usize> {
+  source: Box<dyn Generator<N>>,
+  label: Option<String>,
+}
+
+impl<N: Number> NotGenerator<N> {
+  pub fn new(source: Box<dyn Generator<N>>) -> Self {
+    NotGenerator {
+      source,
+      label: None,
+    }
+  }
+
+  /// Replaces the description built from the source in `generator_info`.
+  pub fn with_label(mut self, label: &str) -> Self {
+    self.label = Some(label.to_string());
+    self
+  }
+}
+
+impl<N: Number> Generator<N> for NotGenerator<N> {
+  fn data_num(&self) -> usize {
+    self.source.data_num()
+  }
+
+  fn next(&mut self) -> std::option::Option<(N, bool)> {
+    self.source.next().map(|(n, b)| (n, !b))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    if let Some(label) = &self.label {
+      return format!("generator = {}", label);
+    }
+
+    format!("!({})", self.source.generator_info())
+  }
+}
+
+/// Builds the generator a source of a combination names.
+pub type Leaf<'a, N> =
+  dyn FnMut(&str) -> Result<Box<dyn Generator<N>>, Box<dyn std::error::Error>> + 'a;
+
+/// Tells whether `expr` combines generators rather than naming a single one.
+pub fn is_combination(expr: &str) -> bool {
+  expr.contains(|c| "&|^!()".contains(c))
+}
+
+/// Builds a generator from an expression such as `primes & !(squares | times:3)`.
+///
+/// `!` binds tightest, then `&`, `^` and `|`. Any other run of characters up
+/// to whitespace, an operator or a parenthesis is a source, built by `leaf`.
+pub fn parse_combination<N: Number + 'static>(
+  expr: &str,
+  leaf: &mut Leaf<N>,
+) -> Result<Box<dyn Generator<N>>, Box<dyn std::error::Error>> {
+  let mut parser = Parser { expr, pos: 0, leaf };
+
+  let gen = parser.parse_binary(0)?;
+  parser.skip_whitespace();
+  if parser.pos < expr.len() {
+    return Err(format!("Unexpected character at {} in '{}'", parser.pos, expr).into());
+  }
+
+  Ok(gen.0)
+}
+
+// Operators from the loosest binding.
+const PRECEDENCE: [Operator; 3] = [Operator::Or, Operator::Xor, Operator::And];
+
+type Parsed<N> = (Box<dyn Generator<N>>, String);
+
+struct Parser<'a, 'b, N> {
+  expr: &'a str,
+  pos: usize,
+  leaf: &'b mut Leaf<'b, N>,
+}
+
+impl<'a, 'b, N: Number + 'static> Parser<'a, 'b, N> {
+  fn skip_whitespace(&mut self) {
+    let rest = &self.expr[self.pos..];
+    self.pos += rest.len() - rest.trim_start().len();
+  }
+
+  fn peek(&mut self) -> Option<char> {
+    self.skip_whitespace();
+    self.expr[self.pos..].chars().next()
+  }
+
+  fn parse_binary(&mut self, level: usize) -> Result<Parsed<N>, Box<dyn std::error::Error>> {
+    if level == PRECEDENCE.len() {
+      return self.parse_unary();
+    }
+
+    let operator = PRECEDENCE[level];
+    let mut operands = vec![self.parse_binary(level + 1)?];
+    while self.peek() == operator.symbol().chars().next() {
+      self.pos += 1;
+      operands.push(self.parse_binary(level + 1)?);
+    }
+
+    if operands.len() == 1 {
+      return Ok(operands.pop().unwrap());
+    }
+
+    let labels: Vec<String> = operands.iter().map(|(_, label)| label.clone()).collect();
+    let label = labels.join(&format!(" {} ", operator.symbol()));
+    let sources = operands.into_iter().map(|(gen, _)| gen).collect();
+    let gen = CombinedGenerator::new(operator, sources)?.with_label(&label);
+    Ok((Box::new(gen), label))
+  }
+
+  fn parse_unary(&mut self) -> Result<Parsed<N>, Box<dyn std::error::Error>> {
+    match self.peek() {
+      Some('!') => {
+        self.pos += 1;
+        let (gen, label) = self.parse_unary()?;
+        let label = format!("!{}", label);
+        let gen = NotGenerator::new(gen).with_label(&label);
+        Ok((Box::new(gen), label))
+      }
+      Some('(') => {
+        self.pos += 1;
+        let (gen, label) = self.parse_binary(0)?;
+        if self.peek() != Some(')') {
+          return Err(format!("Missing ')' at {} in '{}'", self.pos, self.expr).into());
+        }
+
+        self.pos += 1;
+        Ok((gen, format!("({})", label)))
+      }
+      _ => {
+        let rest = &self.expr[self.pos..];
+        let len = rest
+          .find(|c: char| c.is_whitespace() || "&|^!()".contains(c))
+          .unwrap_or(rest.len());
+        if len == 0 {
+          return Err(format!("Missing generator at {} in '{}'", self.pos, self.expr).into());
+        }
+
+        let name = &rest[..len];
+        self.pos += len;
+        Ok(((self.leaf)(name)?, name.to_string()))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ulam::generator::primes::PrimesGenerator;
+  use crate::ulam::generator::squares::SquareGenerator;
+  use crate::ulam::generator::times::TimesGenerator;
+
+  fn leaf(name: &str) -> Result<Box<dyn Generator>, Box<dyn std::error::Error>> {
+    match name {
+      "primes" => Ok(Box::new(PrimesGenerator::new(30, 1))),
+      "squares" => Ok(Box::new(SquareGenerator::new(30, 1))),
+      "times:3" => Ok(Box::new(TimesGenerator::new(30, 3, 1))),
+      "times:5" => Ok(Box::new(TimesGenerator::new(30, 5, 1))),
+      "short" => Ok(Box::new(TimesGenerator::new(20, 5, 1))),
+      _ => Err(format!("Unknown generator({})", name).into()),
+    }
+  }
+
+  fn marked(gen: &mut dyn Generator) -> Vec<usize> {
+    std::iter::from_fn(|| gen.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect()
+  }
+
+  #[test]
+  fn test_combine() {
+    let mut gen = parse_combination("times:3 ^ times:5", &mut leaf).unwrap();
+    assert_eq!(
+      marked(gen.as_mut()),
+      vec![3, 5, 6, 9, 10, 12, 18, 20, 21, 24, 25, 27]
+    );
+
+    let mut gen = parse_combination("squares|primes&!times:3", &mut leaf).unwrap();
+    assert_eq!(
+      marked(gen.as_mut()),
+      vec![1, 2, 4, 5, 7, 9, 11, 13, 16, 17, 19, 23, 25, 29]
+    );
+
+    let mut gen = parse_combination("(squares | primes) & !times:3", &mut leaf).unwrap();
+    assert_eq!(
+      marked(gen.as_mut()),
+      vec![1, 2, 4, 5, 7, 11, 13, 16, 17, 19, 23, 25, 29]
+    );
+    assert_eq!(
+      gen.generator_info(),
+      "generator = (squares | primes) & !times:3"
+    );
+  }
+
+  #[test]
+  fn test_errors() {
+    for expr in &[
+      "primes &",
+      "(primes | squares",
+      "primes squares",
+      "primes & cubes",
+    ] {
+      assert!(parse_combination(expr, &mut leaf).is_err(), "{}", expr);
+    }
+
+    assert!(parse_combination("primes & short", &mut leaf).is_err());
+    assert!(CombinedGenerator::new(
+      Operator::Or,
+      vec![
+        Box::new(PrimesGenerator::new(30, 1)),
+        Box::new(PrimesGenerator::new(31, 2))
+      ]
+    )
+    .is_err());
+    assert!(CombinedGenerator::new(Operator::And, vec![leaf("primes").unwrap()]).is_err());
+  }
+}
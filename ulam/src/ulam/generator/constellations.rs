@@ -0,0 +1,225 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::primes::PrimesGenerator;
+use crate::ulam::generator::sieve::primes_up_to;
+use std::collections::VecDeque;
+
+/// Marks members of prime constellations, primes p + o for every offset o of
+/// a pattern such as `0,2` (twin primes) or `0,2,6`. Each pattern is its own
+/// category; a number in several patterns takes the first.
+///
+/// Membership depends on primes up to the largest offset away, so primality
+/// is read from a window running that far ahead of n.
+pub struct ConstellationGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  patterns: Vec<Vec<usize>>,
+  reach: usize,
+  primes: PrimesGenerator,
+  window: VecDeque<(usize, bool)>,
+  // Instances of each pattern starting at the numbers yielded so far.
+  counts: Vec<usize>,
+}
+
+impl ConstellationGenerator {
+  pub fn new(
+    n: usize,
+    skip: usize,
+    patterns: Vec<Vec<usize>>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    if patterns.is_empty() {
+      return Err("no constellation pattern".into());
+    }
+
+    for pattern in &patterns {
+      check_pattern(pattern)?;
+    }
+
+    let reach = patterns.iter().map(|p| p[p.len() - 1]).max().unwrap();
+    Ok(ConstellationGenerator {
+      i: skip,
+      max: n,
+      skip,
+      primes: PrimesGenerator::new(n.saturating_add(reach), skip.saturating_sub(reach)),
+      counts: vec![0; patterns.len()],
+      patterns,
+      reach,
+      window: VecDeque::new(),
+    })
+  }
+
+  /// `patterns` lists offsets separated by `,`, and patterns by `;`, such as
+  /// `0,2;0,4`.
+  pub fn from_gp(gp: &str, patterns: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    let patterns = patterns
+      .split(";")
+      .map(|p| p.split(",").map(|o| o.trim().parse()).collect())
+      .collect::<Result<_, _>>()?;
+
+    Self::new(to, from, patterns)
+  }
+
+  fn is_prime(&self, n: usize) -> bool {
+    let start = self.window[0].0;
+    n >= start && self.window[n - start].1
+  }
+}
+
+/// Tells why `pattern` is not an admissible constellation: offsets must rise
+/// from 0 and, for every prime q, miss some residue mod q. Otherwise one of
+/// p + o is divisible by q for all p.
+pub fn check_pattern(pattern: &[usize]) -> Result<(), Box<dyn std::error::Error>> {
+  if pattern.len() < 2 || pattern[0] != 0 || pattern.windows(2).any(|w| w[0] >= w[1]) {
+    return Err(format!("pattern must rise from 0({:?})", pattern).into());
+  }
+
+  for q in primes_up_to(pattern.len()) {
+    let mut covered = vec![false; q];
+    for &o in pattern {
+      covered[o % q] = true;
+    }
+
+    if covered.iter().all(|&c| c) {
+      return Err(format!("pattern is not admissible mod {}({:?})", q, pattern).into());
+    }
+  }
+
+  Ok(())
+}
+
+fn label(pattern: &[usize]) -> String {
+  let offsets: Vec<String> = pattern.iter().map(|o| o.to_string()).collect();
+  format!("({})", offsets.join(","))
+}
+
+impl Generator for ConstellationGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    let mut categories = vec![String::new()];
+    categories.extend(self.patterns.iter().map(|p| label(p)));
+    categories
+  }
+
+  fn next_category(&mut self) -> Option<(usize, usize)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    while self.window.back().is_none_or(|&(n, _)| n < i + self.reach) {
+      match self.primes.next() {
+        Some(x) => self.window.push_back(x),
+        None => break,
+      }
+    }
+    while self.window[0].0 + self.reach < i {
+      self.window.pop_front();
+    }
+
+    for (c, pattern) in self.patterns.iter().enumerate() {
+      if pattern.iter().all(|&o| self.is_prime(i + o)) {
+        self.counts[c] += 1;
+      }
+    }
+
+    for (c, pattern) in self.patterns.iter().enumerate() {
+      let member = pattern
+        .iter()
+        .any(|&o| i >= o && pattern.iter().all(|&p| self.is_prime(i - o + p)));
+      if member {
+        return Some((i, c + 1));
+      }
+    }
+
+    Some((i, 0))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    let counts: Vec<String> = self
+      .patterns
+      .iter()
+      .zip(&self.counts)
+      .map(|(p, count)| format!("{} = {}", label(p), count))
+      .collect();
+    format!(
+      "generator = constellations, from = {}, to = {}, {}",
+      self.skip,
+      self.max,
+      counts.join(", ")
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn members(gen: &mut ConstellationGenerator) -> Vec<(usize, usize)> {
+    std::iter::from_fn(|| gen.next_category())
+      .filter(|&(_, c)| c != 0)
+      .collect()
+  }
+
+  #[test]
+  fn test_twins() {
+    let mut gen = ConstellationGenerator::new(32, 4, vec![vec![0, 2]]).unwrap();
+    let members: Vec<usize> = members(&mut gen).iter().map(|&(n, _)| n).collect();
+    assert_eq!(members, vec![5, 7, 11, 13, 17, 19, 29, 31]);
+    assert_eq!(
+      gen.generator_info(),
+      "generator = constellations, from = 4, to = 32, (0,2) = 4"
+    );
+  }
+
+  #[test]
+  fn test_patterns() {
+    let mut gen = ConstellationGenerator::from_gp("0:30", "0,2,6;0,4").unwrap();
+    assert_eq!(
+      members(&mut gen),
+      vec![
+        (3, 2),
+        (5, 1),
+        (7, 1),
+        (11, 1),
+        (13, 1),
+        (17, 1),
+        (19, 1),
+        (23, 1)
+      ]
+    );
+    assert_eq!(gen.categories(), vec!["", "(0,2,6)", "(0,4)"]);
+    assert_eq!(
+      gen.generator_info(),
+      "generator = constellations, from = 0, to = 30, (0,2,6) = 3, (0,4) = 4"
+    );
+  }
+
+  #[test]
+  fn test_check_pattern() {
+    assert!(check_pattern(&[0, 2, 6, 8]).is_ok());
+    assert!(check_pattern(&[0, 2, 4]).is_err());
+    assert!(check_pattern(&[0, 1]).is_err());
+    assert!(check_pattern(&[2, 4]).is_err());
+    assert!(check_pattern(&[0, 6, 2]).is_err());
+  }
+}
@@ -0,0 +1,250 @@
+use crate::ulam::generator::generator::Generator;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Property {
+  /// n that reads the same reversed in the base.
+  Palindrome,
+  /// n whose iterated sum of squared digits reaches 1.
+  Happy,
+  /// n divisible by its digit sum, also called Niven numbers.
+  Harshad,
+  /// n with an even digit sum, an even popcount in base 2.
+  Evil,
+  /// n with an odd digit sum, an odd popcount in base 2.
+  Odious,
+  /// n by the digit sum modulo the base, the Thue–Morse sequence in base 2.
+  ThueMorse,
+}
+
+impl Property {
+  pub fn from_name(name: &str) -> Option<Property> {
+    match name {
+      "palindromes" => Some(Property::Palindrome),
+      "happy" => Some(Property::Happy),
+      "harshad" => Some(Property::Harshad),
+      "evil" => Some(Property::Evil),
+      "odious" => Some(Property::Odious),
+      "thue-morse" => Some(Property::ThueMorse),
+      _ => None,
+    }
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      Property::Palindrome => "palindromes",
+      Property::Happy => "happy",
+      Property::Harshad => "harshad",
+      Property::Evil => "evil",
+      Property::Odious => "odious",
+      Property::ThueMorse => "thue-morse",
+    }
+  }
+
+  fn default_base(&self) -> usize {
+    match self {
+      Property::Evil | Property::Odious | Property::ThueMorse => 2,
+      _ => 10,
+    }
+  }
+}
+
+/// Sum of the digits of `n` in `base`.
+pub fn digit_sum(mut n: u128, base: u128) -> u128 {
+  let mut sum = 0;
+  while n > 0 {
+    sum += n % base;
+    n /= base;
+  }
+
+  sum
+}
+
+/// `n` with its digits in `base` reversed.
+pub fn reverse(mut n: u128, base: u128) -> u128 {
+  let mut r = 0;
+  while n > 0 {
+    r = r * base + n % base;
+    n /= base;
+  }
+
+  r
+}
+
+fn square_digit_sum(mut n: u128, base: u128) -> u128 {
+  let mut sum = 0;
+  while n > 0 {
+    sum += (n % base) * (n % base);
+    n /= base;
+  }
+
+  sum
+}
+
+// Iterating the squared digit sum ends in a cycle, which holds 1 only for
+// happy numbers. The cycle is found by Floyd's tortoise and hare.
+fn is_happy(n: u128, base: u128) -> bool {
+  let mut slow = n;
+  let mut fast = square_digit_sum(n, base);
+  while slow != fast {
+    slow = square_digit_sum(slow, base);
+    fast = square_digit_sum(square_digit_sum(fast, base), base);
+  }
+
+  slow == 1
+}
+
+/// Marks n by a property of its digits in a base.
+pub struct DigitsGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  property: Property,
+  base: u128,
+}
+
+impl DigitsGenerator {
+  pub fn new(n: usize, skip: usize, property: Property, base: usize) -> Self {
+    DigitsGenerator {
+      i: skip,
+      max: n,
+      skip,
+      property,
+      base: base as u128,
+    }
+  }
+
+  /// `gp` is `from:to:base`. The base is 2 by default for evil, odious and
+  /// Thue–Morse, and 10 otherwise.
+  pub fn from_gp(gp: &str, property: Property) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+    let base = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    let base: usize = if let Some(base) = base {
+      base.parse()?
+    } else {
+      property.default_base()
+    };
+
+    if base < 2 {
+      return Err("base must be at least 2".into());
+    }
+
+    Ok(Self::new(to, from, property, base))
+  }
+}
+
+impl Generator for DigitsGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    match self.property {
+      Property::ThueMorse => {
+        let mut categories = vec![String::new()];
+        categories.extend((0..self.base).map(|t| format!("t = {}", t)));
+        categories
+      }
+      _ => vec!["false".to_string(), "true".to_string()],
+    }
+  }
+
+  fn next_category(&mut self) -> Option<(usize, usize)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    let (n, base) = (i as u128, self.base);
+    let category = match self.property {
+      Property::Palindrome => (reverse(n, base) == n) as usize,
+      Property::Happy => (n > 0 && is_happy(n, base)) as usize,
+      Property::Harshad => (n > 0 && n.is_multiple_of(digit_sum(n, base))) as usize,
+      Property::Evil => digit_sum(n, base).is_multiple_of(2) as usize,
+      Property::Odious => !digit_sum(n, base).is_multiple_of(2) as usize,
+      Property::ThueMorse => (digit_sum(n, base) % base) as usize + 1,
+    };
+    Some((i, category))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = {}, base = {}, from = {}, to = {}",
+      self.property.name(),
+      self.base,
+      self.skip,
+      self.max
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn marked(property: Property, n: usize, base: usize) -> Vec<usize> {
+    let mut gen = DigitsGenerator::new(n, 0, property, base);
+    std::iter::from_fn(|| gen.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect()
+  }
+
+  #[test]
+  fn test_properties() {
+    assert_eq!(
+      marked(Property::Palindrome, 30, 10),
+      vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 11, 22]
+    );
+    assert_eq!(
+      marked(Property::Palindrome, 20, 2),
+      vec![0, 1, 3, 5, 7, 9, 15, 17]
+    );
+    assert_eq!(
+      marked(Property::Happy, 50, 10),
+      vec![1, 7, 10, 13, 19, 23, 28, 31, 32, 44, 49]
+    );
+    assert_eq!(
+      marked(Property::Harshad, 30, 10),
+      vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 12, 18, 20, 21, 24, 27, 30]
+    );
+    assert_eq!(
+      marked(Property::Evil, 20, 2),
+      vec![0, 3, 5, 6, 9, 10, 12, 15, 17, 18, 20]
+    );
+    assert_eq!(
+      marked(Property::Odious, 20, 2),
+      vec![1, 2, 4, 7, 8, 11, 13, 14, 16, 19]
+    );
+  }
+
+  #[test]
+  fn test_thue_morse() {
+    let mut gen = DigitsGenerator::from_gp("0:7", Property::ThueMorse).unwrap();
+    let categories: Vec<_> = std::iter::from_fn(|| gen.next_category())
+      .map(|(_, c)| c)
+      .collect();
+    assert_eq!(categories, vec![1, 2, 2, 1, 2, 1, 1, 2]);
+    assert_eq!(gen.categories(), vec!["", "t = 0", "t = 1"]);
+    assert_eq!(
+      gen.generator_info(),
+      "generator = thue-morse, base = 2, from = 0, to = 7"
+    );
+    assert!(DigitsGenerator::from_gp("0:7:1", Property::Happy).is_err());
+  }
+}
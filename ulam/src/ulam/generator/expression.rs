@@ -0,0 +1,469 @@
+use crate::ulam::generator::digits::digit_sum;
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::gcd;
+use crate::ulam::generator::number::Number;
+use crate::ulam::generator::primality::is_prime;
+use std::convert::TryFrom;
+
+const FUNCTIONS: [(&str, usize); 9] = [
+  ("is_prime", 1),
+  ("is_square", 1),
+  ("omega", 1),
+  ("bigomega", 1),
+  ("phi", 1),
+  ("mu", 1),
+  ("digit_sum", 1),
+  ("popcount", 1),
+  ("gcd", 2),
+];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum BinaryOp {
+  Or,
+  And,
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  Add,
+  Sub,
+  Mul,
+  Div,
+  Rem,
+}
+
+// Binary operators from the loosest binding, each level left associative.
+const LEVELS: [&[(&str, BinaryOp)]; 5] = [
+  &[("||", BinaryOp::Or)],
+  &[("&&", BinaryOp::And)],
+  &[
+    ("==", BinaryOp::Eq),
+    ("!=", BinaryOp::Ne),
+    ("<=", BinaryOp::Le),
+    (">=", BinaryOp::Ge),
+    ("<", BinaryOp::Lt),
+    (">", BinaryOp::Gt),
+  ],
+  &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+  &[
+    ("*", BinaryOp::Mul),
+    ("/", BinaryOp::Div),
+    ("%", BinaryOp::Rem),
+  ],
+];
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+  N,
+  Literal(i128),
+  Not(Box<Expr>),
+  Neg(Box<Expr>),
+  Binary(BinaryOp, Box<Expr>, Box<Expr>),
+  Call(&'static str, Vec<Expr>),
+}
+
+impl Expr {
+  /// Value of the expression, or `None` on overflow or division by zero.
+  /// Comparisons and logical operators give 1 or 0.
+  fn eval(&self, n: i128) -> Option<i128> {
+    match self {
+      Expr::N => Some(n),
+      Expr::Literal(v) => Some(*v),
+      Expr::Not(e) => Some((e.eval(n)? == 0) as i128),
+      Expr::Neg(e) => e.eval(n)?.checked_neg(),
+      Expr::Binary(BinaryOp::Or, a, b) => Some((a.eval(n)? != 0 || b.eval(n)? != 0) as i128),
+      Expr::Binary(BinaryOp::And, a, b) => Some((a.eval(n)? != 0 && b.eval(n)? != 0) as i128),
+      Expr::Binary(op, a, b) => {
+        let (a, b) = (a.eval(n)?, b.eval(n)?);
+        match op {
+          BinaryOp::Eq => Some((a == b) as i128),
+          BinaryOp::Ne => Some((a != b) as i128),
+          BinaryOp::Lt => Some((a < b) as i128),
+          BinaryOp::Le => Some((a <= b) as i128),
+          BinaryOp::Gt => Some((a > b) as i128),
+          BinaryOp::Ge => Some((a >= b) as i128),
+          BinaryOp::Add => a.checked_add(b),
+          BinaryOp::Sub => a.checked_sub(b),
+          BinaryOp::Mul => a.checked_mul(b),
+          BinaryOp::Div => a.checked_div_euclid(b),
+          BinaryOp::Rem => a.checked_rem_euclid(b),
+          BinaryOp::Or | BinaryOp::And => unreachable!(),
+        }
+      }
+      Expr::Call(name, args) => {
+        let args: Vec<i128> = args.iter().map(|e| e.eval(n)).collect::<Option<_>>()?;
+        call(name, &args)
+      }
+    }
+  }
+}
+
+fn call(name: &str, args: &[i128]) -> Option<i128> {
+  let x = args[0].unsigned_abs();
+  match name {
+    "is_prime" => Some((args[0] > 0 && is_prime(&x)) as i128),
+    "is_square" => Some((args[0] >= 0 && x.isqrt() * x.isqrt() == x) as i128),
+    "omega" => Some(factorize(x).len() as i128),
+    "bigomega" => Some(factorize(x).iter().map(|&(_, e)| e as i128).sum()),
+    "phi" => {
+      let phi = factorize(x)
+        .iter()
+        .fold(x, |phi, &(p, _)| phi / p * (p - 1));
+      i128::try_from(phi).ok()
+    }
+    "mu" => {
+      let factors = factorize(x);
+      if x == 0 || factors.iter().any(|&(_, e)| e > 1) {
+        Some(0)
+      } else if factors.len().is_multiple_of(2) {
+        Some(1)
+      } else {
+        Some(-1)
+      }
+    }
+    "digit_sum" => Some(digit_sum(x, 10) as i128),
+    "popcount" => Some(x.count_ones() as i128),
+    "gcd" => i128::try_from(gcd(x, args[1].unsigned_abs())).ok(),
+    _ => unreachable!(),
+  }
+}
+
+// Primes below this are found by trial division, larger ones by Pollard's rho.
+const TRIAL_DIVISION_BOUND: u128 = 1000;
+
+// Prime factorization of x in increasing order. 0 and 1 have no factors.
+fn factorize(mut x: u128) -> Vec<(u128, u32)> {
+  let mut factors = vec![];
+  let mut p = 2;
+  while x > 1 && p < TRIAL_DIVISION_BOUND && p <= x / p {
+    let mut e = 0;
+    while x.is_multiple_of(p) {
+      x /= p;
+      e += 1;
+    }
+    if e > 0 {
+      factors.push((p, e));
+    }
+    p += if p == 2 { 1 } else { 2 };
+  }
+
+  let mut rest = vec![];
+  let mut composites = vec![x];
+  while let Some(x) = composites.pop() {
+    if x <= 1 {
+      continue;
+    }
+
+    if is_prime(&x) {
+      rest.push(x);
+    } else {
+      let d = pollard_rho(x);
+      composites.push(d);
+      composites.push(x / d);
+    }
+  }
+
+  rest.sort_unstable();
+  for p in rest {
+    match factors.last_mut() {
+      Some((q, e)) if *q == p => *e += 1,
+      _ => factors.push((p, 1)),
+    }
+  }
+
+  factors
+}
+
+// A proper divisor of a composite x with no factor below
+// TRIAL_DIVISION_BOUND, by Pollard's rho with Floyd's cycle detection.
+fn pollard_rho(x: u128) -> u128 {
+  for c in 1.. {
+    let f = |y: u128| {
+      let y = y.mul_mod(&y, &x);
+      if y >= x - c {
+        y - (x - c)
+      } else {
+        y + c
+      }
+    };
+
+    let (mut slow, mut fast) = (2, 2);
+    loop {
+      slow = f(slow);
+      fast = f(f(fast));
+      let d = gcd(slow.abs_diff(fast), x);
+      if d == x {
+        break;
+      }
+      if d != 1 {
+        return d;
+      }
+    }
+  }
+
+  unreachable!()
+}
+
+struct Parser<'a> {
+  src: &'a str,
+  pos: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn error(&self, message: &str) -> String {
+    format!("{} at {} in '{}'", message, self.pos, self.src)
+  }
+
+  fn rest(&mut self) -> &'a str {
+    let rest = &self.src[self.pos..];
+    self.pos += rest.len() - rest.trim_start().len();
+    &self.src[self.pos..]
+  }
+
+  fn eat(&mut self, token: &str) -> bool {
+    if self.rest().starts_with(token) {
+      self.pos += token.len();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn expect(&mut self, token: &str) -> Result<(), String> {
+    if self.eat(token) {
+      Ok(())
+    } else {
+      Err(self.error(&format!("Expected '{}'", token)))
+    }
+  }
+
+  fn parse_binary(&mut self, level: usize) -> Result<Expr, String> {
+    if level == LEVELS.len() {
+      return self.parse_unary();
+    }
+
+    let mut lhs = self.parse_binary(level + 1)?;
+    'outer: loop {
+      for &(token, op) in LEVELS[level] {
+        if self.eat(token) {
+          let rhs = self.parse_binary(level + 1)?;
+          lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+          continue 'outer;
+        }
+      }
+
+      return Ok(lhs);
+    }
+  }
+
+  fn parse_unary(&mut self) -> Result<Expr, String> {
+    // "!=" can't start an operand, so it isn't read as "!" and "=".
+    if self.rest().starts_with("!=") {
+      return Err(self.error("Unexpected '!='"));
+    }
+    if self.eat("!") {
+      return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+    }
+    if self.eat("-") {
+      return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+    }
+    if self.eat("(") {
+      let e = self.parse_binary(0)?;
+      self.expect(")")?;
+      return Ok(e);
+    }
+
+    let rest = self.rest();
+    let len = rest
+      .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+      .unwrap_or(rest.len());
+    let word = &rest[..len];
+    if word.is_empty() {
+      return Err(self.error("Expected a value"));
+    }
+
+    if word.as_bytes()[0].is_ascii_digit() {
+      let v = word.parse().map_err(|_| self.error("Invalid number"))?;
+      self.pos += len;
+      return Ok(Expr::Literal(v));
+    }
+
+    if word == "n" {
+      self.pos += len;
+      return Ok(Expr::N);
+    }
+
+    let &(name, arity) = FUNCTIONS
+      .iter()
+      .find(|&&(name, _)| name == word)
+      .ok_or_else(|| self.error(&format!("Unknown name '{}'", word)))?;
+    self.pos += len;
+
+    self.expect("(")?;
+    let mut args = vec![self.parse_binary(0)?];
+    while self.eat(",") {
+      args.push(self.parse_binary(0)?);
+    }
+    self.expect(")")?;
+
+    if args.len() != arity {
+      return Err(self.error(&format!("{} takes {} argument(s)", name, arity)));
+    }
+
+    Ok(Expr::Call(name, args))
+  }
+}
+
+fn parse(src: &str) -> Result<Expr, String> {
+  let mut parser = Parser { src, pos: 0 };
+  let e = parser.parse_binary(0)?;
+  if !parser.rest().is_empty() {
+    return Err(parser.error("Unexpected character"));
+  }
+
+  Ok(e)
+}
+
+/// Marks n for which a predicate such as `is_prime(n) && digit_sum(n) % 3 == 1`
+/// is non-zero.
+///
+/// The language has integer literals, `n`, arithmetic (`+ - * / %`),
+/// comparisons, `&& || !`, parentheses and the functions `is_prime`,
+/// `is_square`, `omega`, `bigomega`, `phi`, `mu`, `digit_sum`, `popcount` and `gcd`.
+/// Division rounds down and `%` is never negative. Where the predicate
+/// overflows or divides by zero, n is left unmarked.
+pub struct ExpressionGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  src: String,
+  expr: Expr,
+}
+
+impl ExpressionGenerator {
+  pub fn new(n: usize, skip: usize, src: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    Ok(ExpressionGenerator {
+      i: skip,
+      max: n,
+      skip,
+      src: src.trim().to_string(),
+      expr: parse(src)?,
+    })
+  }
+
+  pub fn from_gp(gp: &str, src: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    Self::new(to, from, src)
+  }
+}
+
+impl Generator for ExpressionGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    let v = self.expr.eval(i as i128);
+    Some((i, v.is_some_and(|v| v != 0)))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = {}, from = {}, to = {}",
+      self.src, self.skip, self.max
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn eval(src: &str, n: i128) -> Option<i128> {
+    parse(src).unwrap().eval(n)
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(eval("1 + 2 * 3 - -4", 0), Some(11));
+    assert_eq!(eval("(1 + 2) * 3", 0), Some(9));
+    assert_eq!(eval("n % 4 == 1 || n < 3 && !(n == 2)", 5), Some(1));
+    assert_eq!(eval("n % 4 == 1 || n < 3 && !(n == 2)", 2), Some(0));
+    assert_eq!(eval("n != 3", 3), Some(0));
+    assert_eq!(eval("-7 / 2 + -7 % 2", 0), Some(-3));
+    assert_eq!(eval("n / (n - 1)", 1), None);
+
+    for src in &["", "n +", "(n", "m", "gcd(n)", "is_prime n", "n ! 2", "1 2"] {
+      assert!(parse(src).is_err(), "{}", src);
+    }
+  }
+
+  #[test]
+  fn test_functions() {
+    assert_eq!(eval("is_prime(n)", 97), Some(1));
+    assert_eq!(eval("is_prime(n)", 91), Some(0));
+    assert_eq!(eval("is_prime(n)", -7), Some(0));
+    assert_eq!(eval("is_square(n)", 144), Some(1));
+    assert_eq!(eval("is_square(n)", 145), Some(0));
+    assert_eq!(eval("omega(n)", 360), Some(3));
+    assert_eq!(eval("bigomega(n)", 360), Some(6));
+    assert_eq!(eval("phi(n)", 36), Some(12));
+    assert_eq!(eval("phi(n)", 1), Some(1));
+    assert_eq!(eval("mu(n)", 30), Some(-1));
+    assert_eq!(eval("mu(n)", 6), Some(1));
+    assert_eq!(eval("mu(n)", 12), Some(0));
+    assert_eq!(eval("mu(n)", 1), Some(1));
+    assert_eq!(eval("digit_sum(n)", 9875), Some(29));
+    assert_eq!(eval("popcount(n)", 255), Some(8));
+    assert_eq!(eval("gcd(n, 84)", 36), Some(12));
+  }
+
+  #[test]
+  fn test_factorize() {
+    assert_eq!(factorize(0), vec![]);
+    assert_eq!(factorize(1), vec![]);
+    assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+
+    let (p, q) = (1_000_000_007u128, 998_244_353u128);
+    assert_eq!(factorize(p), vec![(p, 1)]);
+    assert_eq!(
+      factorize(12 * p * p * q),
+      vec![(2, 2), (3, 1), (q, 1), (p, 2)]
+    );
+
+    let m = (1u128 << 127) - 1; // a Mersenne prime
+    assert_eq!(factorize(m), vec![(m, 1)]);
+    assert_eq!(eval("omega(n)", i128::MAX), Some(1));
+  }
+
+  #[test]
+  fn test_generate() {
+    let mut ite =
+      ExpressionGenerator::from_gp("10:20", "is_prime(n) && digit_sum(n) % 3 == 1").unwrap();
+    let marked: Vec<usize> = std::iter::from_fn(|| ite.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect();
+    assert_eq!(marked, vec![13, 19]);
+    assert!(ExpressionGenerator::from_gp("1:10", "is_prime(n) &&").is_err());
+  }
+}
@@ -0,0 +1,160 @@
+use crate::ulam::generator::sieve::primes_up_to;
+
+const SEGMENT_LEN: usize = 1 << 14;
+
+/// Largest number a `FactorSieve` factorizes. Its base primes, those up to
+/// `sqrt(max)`, are sieved up front, which stops being cheap beyond this.
+pub const FACTOR_LIMIT: usize = 1 << 40;
+
+/// Segmented sieve that factorizes every number of a window.
+///
+/// This is not a smallest-prime-factor table, which would need an entry for
+/// every number from 0 and so could not reach a window far from it. Instead
+/// each base prime steps through its multiples in the segment and divides
+/// them out, and whatever is left above 1 is the one prime factor beyond
+/// `sqrt(max)`. Like `SegmentedSieve`, only one segment is held at a time,
+/// so memory is bounded by the segment length plus the base primes.
+pub struct FactorSieve {
+  max: usize,
+  primes: Vec<usize>,
+  segment_len: usize,
+  low: usize,
+  high: usize,
+  factors: Vec<Vec<(usize, u32)>>,
+  rest: Vec<usize>,
+}
+
+impl FactorSieve {
+  /// Fails when `max` is above `FACTOR_LIMIT`, for `from_gp` to call before
+  /// building a sieve.
+  pub fn check(max: usize) -> Result<(), Box<dyn std::error::Error>> {
+    if max > FACTOR_LIMIT {
+      return Err(format!("Can't factorize beyond {}({})", FACTOR_LIMIT, max).into());
+    }
+
+    Ok(())
+  }
+
+  pub fn new(max: usize) -> Self {
+    Self::with_segment_len(max, SEGMENT_LEN)
+  }
+
+  fn with_segment_len(max: usize, segment_len: usize) -> Self {
+    FactorSieve {
+      max,
+      primes: primes_up_to(max.isqrt()),
+      segment_len,
+      low: 0,
+      high: 0,
+      factors: vec![vec![]; segment_len],
+      rest: vec![0; segment_len],
+    }
+  }
+
+  /// Prime factorization of `n` as (prime, exponent) pairs in increasing
+  /// order. 0 and 1 have no factors.
+  pub fn factorize(&mut self, n: usize) -> &[(usize, u32)] {
+    debug_assert!(
+      n <= self.max,
+      "{} is beyond the sieve limit {}",
+      n,
+      self.max
+    );
+
+    if n < self.low || self.high <= n {
+      self.sieve_segment(n);
+    }
+
+    &self.factors[n - self.low]
+  }
+
+  fn sieve_segment(&mut self, low: usize) {
+    let high = low.saturating_add(self.segment_len);
+    self.low = low;
+    self.high = high;
+    for (i, rest) in self.rest.iter_mut().enumerate() {
+      *rest = low + i;
+    }
+    for factors in self.factors.iter_mut() {
+      factors.clear();
+    }
+
+    for &p in &self.primes {
+      if p * p >= high {
+        break;
+      }
+
+      let mut m = low.div_ceil(p).max(1) * p;
+      while m < high {
+        let i = m - low;
+        let mut e = 0;
+        while self.rest[i].is_multiple_of(p) {
+          self.rest[i] /= p;
+          e += 1;
+        }
+        self.factors[i].push((p, e));
+        m += p;
+      }
+    }
+
+    for (factors, &rest) in self.factors.iter_mut().zip(self.rest.iter()) {
+      if rest > 1 {
+        factors.push((rest, 1));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn factorize_naive(mut n: usize) -> Vec<(usize, u32)> {
+    let mut factors = vec![];
+    let mut p = 2;
+    while n > 1 && p * p <= n {
+      let mut e = 0;
+      while n.is_multiple_of(p) {
+        n /= p;
+        e += 1;
+      }
+      if e > 0 {
+        factors.push((p, e));
+      }
+      p += 1;
+    }
+    if n > 1 {
+      factors.push((n, 1));
+    }
+    factors
+  }
+
+  #[test]
+  fn test_factorize() {
+    let max = 5000;
+    let mut sieve = FactorSieve::with_segment_len(max, 64);
+    for n in 0..=max {
+      assert_eq!(sieve.factorize(n), &factorize_naive(n)[..], "n = {}", n);
+    }
+  }
+
+  #[test]
+  fn test_check() {
+    assert!(FactorSieve::check(FACTOR_LIMIT).is_ok());
+    assert!(FactorSieve::check(FACTOR_LIMIT + 1).is_err());
+  }
+
+  #[test]
+  fn test_factorize_far() {
+    let mut sieve = FactorSieve::new(1_000_000_014);
+    assert_eq!(sieve.factorize(1_000_000_007), &[(1_000_000_007, 1)]);
+    assert_eq!(
+      sieve.factorize(1_000_000_014),
+      &[(2, 1), (3, 1), (13, 1), (103, 1), (124_471, 1)]
+    );
+    assert_eq!(
+      sieve.factorize(999_999_999),
+      &[(3, 4), (37, 1), (333_667, 1)]
+    );
+  }
+}
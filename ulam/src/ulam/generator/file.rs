@@ -0,0 +1,169 @@
+use crate::ulam::generator::generator::Generator;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Marks the numbers listed in a file, such as an OEIS b-file
+/// (`index value` per line), a one-column CSV or newline separated numbers.
+///
+/// The last field of each line is the value. Blank lines and `#` comments are
+/// skipped, as is a non-numeric first line, taken as a CSV header. Values
+/// outside the window, including negative ones, are ignored.
+pub struct FileGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  source: String,
+  values: Vec<usize>,
+  pos: usize,
+}
+
+impl FileGenerator {
+  pub fn from_reader(
+    n: usize,
+    skip: usize,
+    source: &str,
+    reader: impl BufRead,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut values = vec![];
+    let mut first = true;
+    for (i, line) in reader.lines().enumerate() {
+      let line = line?;
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let field = line
+        .rsplit(|c: char| c.is_whitespace() || c == ',')
+        .next()
+        .unwrap()
+        .trim_matches('"');
+      let digits = field.strip_prefix('-').unwrap_or(field);
+      let header = first;
+      first = false;
+      if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        if header {
+          continue;
+        }
+
+        return Err(format!("{}:{}: not a number({})", source, i + 1, field).into());
+      }
+
+      if field.starts_with('-') {
+        continue;
+      }
+
+      // Digits that overflow a usize are beyond any window.
+      if let Ok(v) = field.parse::<usize>() {
+        if skip <= v && v <= n {
+          values.push(v);
+        }
+      }
+    }
+
+    values.sort_unstable();
+    values.dedup();
+
+    Ok(FileGenerator {
+      i: skip,
+      max: n,
+      skip,
+      source: source.to_string(),
+      values,
+      pos: 0,
+    })
+  }
+
+  /// Reads `path`, or stdin when it is `-`.
+  pub fn from_gp(gp: &str, path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    if path == "-" {
+      let stdin = std::io::stdin();
+      return Self::from_reader(to, from, "stdin", stdin.lock());
+    }
+
+    let file = File::open(path).map_err(|e| format!("Can't open {}({})", path, e))?;
+    let source = Path::new(path)
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string())
+      .unwrap_or_else(|| path.to_string());
+    Self::from_reader(to, from, &source, BufReader::new(file))
+  }
+}
+
+impl Generator for FileGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    if self.values.get(self.pos) == Some(&i) {
+      self.pos += 1;
+      return Some((i, true));
+    }
+
+    Some((i, false))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = {}, from = {}, to = {}, count = {}",
+      self.source,
+      self.skip,
+      self.max,
+      self.values.len()
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn marked(gen: &mut FileGenerator) -> Vec<usize> {
+    std::iter::from_fn(|| gen.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect()
+  }
+
+  #[test]
+  fn test_b_file() {
+    let text = "# A000290 The squares\n\n0 0\n1 1\n2 4\n3 9\n4 16\n5 25\n";
+    let mut gen = FileGenerator::from_reader(20, 2, "b000290.txt", text.as_bytes()).unwrap();
+    assert_eq!(marked(&mut gen), vec![4, 9, 16]);
+    assert_eq!(
+      gen.generator_info(),
+      "generator = b000290.txt, from = 2, to = 20, count = 3"
+    );
+  }
+
+  #[test]
+  fn test_csv() {
+    let text = "value\n7\r\n-3\n\"5\"\n3\n5\n123456789012345678901234567890\n";
+    let mut gen = FileGenerator::from_reader(10, 0, "values.csv", text.as_bytes()).unwrap();
+    assert_eq!(marked(&mut gen), vec![3, 5, 7]);
+
+    let text = "1\n2\nthree\n";
+    assert!(FileGenerator::from_reader(10, 0, "values.csv", text.as_bytes()).is_err());
+  }
+}
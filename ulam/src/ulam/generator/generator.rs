@@ -1,5 +1,43 @@
-pub trait Generator {
+pub trait Generator<N = usize> {
   fn data_num(&self) -> usize;
   fn generator_info(&self) -> String;
-  fn next(&mut self) -> Option<(usize, bool)>;
+  fn next(&mut self) -> Option<(N, bool)>;
+
+  /// Labels of the categories yielded by `next_category`. Category 0 is the
+  /// background and is left blank.
+  fn categories(&self) -> Vec<String> {
+    vec!["false".to_string(), "true".to_string()]
+  }
+
+  fn next_category(&mut self) -> Option<(N, usize)> {
+    self.next().map(|(n, b)| (n, b as usize))
+  }
+
+  /// Smallest and largest value yielded by `next_value`. Generators that
+  /// return `Some` are drawn as heat maps instead of by category.
+  fn value_range(&self) -> Option<(f64, f64)> {
+    None
+  }
+
+  /// Numeric value of the next number, or `None` to leave it blank.
+  fn next_value(&mut self) -> Option<(N, Option<f64>)> {
+    self
+      .next_category()
+      .map(|(n, c)| (n, if c != 0 { Some(c as f64) } else { None }))
+  }
+}
+
+/// Range of the values `gen` yields, or `None` when it yields none.
+pub fn scan_value_range<N>(gen: &mut dyn Generator<N>) -> Option<(f64, f64)> {
+  let mut range: Option<(f64, f64)> = None;
+  while let Some((_, value)) = gen.next_value() {
+    if let Some(v) = value {
+      range = Some(match range {
+        Some((min, max)) => (min.min(v), max.max(v)),
+        None => (v, v),
+      });
+    }
+  }
+
+  range
 }
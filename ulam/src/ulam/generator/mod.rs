@@ -1,7 +1,28 @@
+pub mod collatz;
+pub mod combinators;
+pub mod constellations;
+pub mod digits;
+pub mod divisors;
+pub mod expression;
+pub mod factors;
+pub mod file;
+#[allow(clippy::module_inception)]
 pub mod generator;
-pub mod prime1s;
-pub mod prime3s;
-pub mod prime7s;
+pub mod multiplicative;
+pub mod number;
+pub mod polygonal;
+pub mod polynomial;
+pub mod primality;
+pub mod prime_factors;
+pub mod prime_gaps;
 pub mod primes;
+pub mod primes_in_class;
+pub mod pseudoprimes;
+pub mod random;
+pub mod residues;
+pub mod sequences;
+pub mod sieve;
+pub mod special_primes;
 pub mod squares;
 pub mod times;
+pub mod totient;
@@ -0,0 +1,224 @@
+use crate::ulam::generator::factors::FactorSieve;
+use crate::ulam::generator::generator::Generator;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Structure {
+  /// n with no square factor.
+  Squarefree,
+  /// n by the Möbius function μ(n).
+  Mobius,
+  /// n with Ω(n) = k, semiprimes for k = 2.
+  AlmostPrime(usize),
+  /// n with no prime factor above B.
+  Smooth(usize),
+}
+
+impl Structure {
+  fn name(&self) -> String {
+    match *self {
+      Structure::Squarefree => "squarefree".to_string(),
+      Structure::Mobius => "mobius".to_string(),
+      Structure::AlmostPrime(k) => format!("almost-primes, k = {}", k),
+      Structure::Smooth(b) => format!("smooth, B = {}", b),
+    }
+  }
+
+  /// The name of the third `gp` field.
+  fn field(&self) -> &'static str {
+    match *self {
+      Structure::AlmostPrime(_) => "k",
+      Structure::Smooth(_) => "B",
+      _ => "",
+    }
+  }
+}
+
+/// Marks n by the shape of its factorization, read from a `FactorSieve`.
+/// The Möbius function has a category for each of μ(n) = 1, -1 and 0.
+pub struct MultiplicativeGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  structure: Structure,
+  sieve: FactorSieve,
+}
+
+impl MultiplicativeGenerator {
+  pub fn new(n: usize, skip: usize, structure: Structure) -> Self {
+    MultiplicativeGenerator {
+      i: skip,
+      max: n,
+      skip,
+      structure,
+      sieve: FactorSieve::new(n),
+    }
+  }
+
+  /// `gp` is `from:to:k` for almost primes and `from:to:B` for smooth
+  /// numbers, where the third field is required.
+  pub fn from_gp(
+    gp: &str,
+    structure: fn(usize) -> Structure,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let (from, to) = parse_range(gp)?;
+    let param: usize = if let Some(param) = gp.split(":").nth(2) {
+      param.parse()?
+    } else {
+      return Err(format!("{} needs a third field", structure(0).field()).into());
+    };
+
+    let structure = structure(param);
+    match structure {
+      Structure::AlmostPrime(0) => return Err("k must be at least 1".into()),
+      Structure::Smooth(b) if b < 2 => return Err("B must be at least 2".into()),
+      _ => {}
+    }
+
+    Ok(Self::new(to, from, structure))
+  }
+
+  /// `gp` is `from:to`, for structures without a parameter, such as the
+  /// squarefree numbers or the semiprimes.
+  pub fn fixed_from_gp(gp: &str, structure: Structure) -> Result<Self, Box<dyn std::error::Error>> {
+    if let Some(param) = gp.split(":").nth(2) {
+      return Err(format!("{} takes no third field({})", structure.name(), param).into());
+    }
+
+    let (from, to) = parse_range(gp)?;
+    Ok(Self::new(to, from, structure))
+  }
+}
+
+fn parse_range(gp: &str) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+  let mut gp = gp.split(":");
+  let from = gp.next();
+  let to = gp.next();
+
+  let from: usize = if let Some(from) = from {
+    from.parse()?
+  } else {
+    1
+  };
+
+  let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+  FactorSieve::check(to)?;
+
+  Ok((from, to))
+}
+
+impl Generator for MultiplicativeGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    match self.structure {
+      Structure::Mobius => vec!["", "μ = 1", "μ = -1", "μ = 0"],
+      _ => vec!["false", "true"],
+    }
+    .into_iter()
+    .map(|c| c.to_string())
+    .collect()
+  }
+
+  fn next_category(&mut self) -> Option<(usize, usize)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    if i == 0 {
+      return Some((i, 0));
+    }
+
+    let factors = self.sieve.factorize(i);
+    let squarefree = factors.iter().all(|&(_, e)| e == 1);
+    let category = match self.structure {
+      Structure::Squarefree => squarefree as usize,
+      Structure::Mobius if !squarefree => 3,
+      Structure::Mobius => 1 + factors.len() % 2,
+      Structure::AlmostPrime(k) => {
+        (factors.iter().map(|&(_, e)| e as usize).sum::<usize>() == k) as usize
+      }
+      Structure::Smooth(b) => factors.last().is_none_or(|&(p, _)| p <= b) as usize,
+    };
+    Some((i, category))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = {}, from = {}, to = {}",
+      self.structure.name(),
+      self.skip,
+      self.max
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn marked(structure: Structure, n: usize) -> Vec<usize> {
+    let mut gen = MultiplicativeGenerator::new(n, 0, structure);
+    std::iter::from_fn(|| gen.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect()
+  }
+
+  #[test]
+  fn test_structures() {
+    assert_eq!(
+      marked(Structure::Squarefree, 20),
+      vec![1, 2, 3, 5, 6, 7, 10, 11, 13, 14, 15, 17, 19]
+    );
+    assert_eq!(
+      marked(Structure::AlmostPrime(2), 30),
+      vec![4, 6, 9, 10, 14, 15, 21, 22, 25, 26]
+    );
+    assert_eq!(
+      marked(Structure::AlmostPrime(3), 50),
+      vec![8, 12, 18, 20, 27, 28, 30, 42, 44, 45, 50]
+    );
+    assert_eq!(
+      marked(Structure::Smooth(5), 30),
+      vec![1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 15, 16, 18, 20, 24, 25, 27, 30]
+    );
+  }
+
+  #[test]
+  fn test_mobius() {
+    let mut gen = MultiplicativeGenerator::fixed_from_gp("0:10", Structure::Mobius).unwrap();
+    let categories: Vec<_> = std::iter::from_fn(|| gen.next_category())
+      .map(|(_, c)| c)
+      .collect();
+    assert_eq!(categories, vec![0, 1, 2, 2, 3, 2, 1, 2, 3, 3, 1]);
+    assert_eq!(gen.categories(), vec!["", "μ = 1", "μ = -1", "μ = 0"]);
+  }
+
+  #[test]
+  fn test_from_gp() {
+    let gen = MultiplicativeGenerator::from_gp("1:100:7", Structure::Smooth).unwrap();
+    assert_eq!(
+      gen.generator_info(),
+      "generator = smooth, B = 7, from = 1, to = 100"
+    );
+    let gen = MultiplicativeGenerator::from_gp("1:100:3", Structure::AlmostPrime).unwrap();
+    assert_eq!(
+      gen.generator_info(),
+      "generator = almost-primes, k = 3, from = 1, to = 100"
+    );
+    assert!(MultiplicativeGenerator::from_gp("1:100", Structure::AlmostPrime).is_err());
+    assert!(MultiplicativeGenerator::from_gp("1:100", Structure::Smooth).is_err());
+    assert!(MultiplicativeGenerator::from_gp("1:100:0", Structure::AlmostPrime).is_err());
+    assert!(MultiplicativeGenerator::from_gp("1:100:1", Structure::Smooth).is_err());
+    assert!(MultiplicativeGenerator::fixed_from_gp("1:100:3", Structure::AlmostPrime(2)).is_err());
+    assert!(MultiplicativeGenerator::fixed_from_gp("1:10000000000000", Structure::Mobius).is_err());
+  }
+}
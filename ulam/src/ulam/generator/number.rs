@@ -0,0 +1,167 @@
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::ops::Add;
+use std::ops::Div;
+use std::ops::Mul;
+use std::ops::Rem;
+use std::ops::Sub;
+
+/// Unsigned integer type that generators can count with.
+pub trait Number:
+  Clone
+  + Ord
+  + Display
+  + Add<Output = Self>
+  + Sub<Output = Self>
+  + Mul<Output = Self>
+  + Div<Output = Self>
+  + Rem<Output = Self>
+{
+  fn from_usize(n: usize) -> Self;
+  fn to_usize(&self) -> Option<usize>;
+  fn to_f64(&self) -> f64;
+  fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error>>;
+  fn isqrt(&self) -> Self;
+  fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+  /// `self * other % m` without overflowing. Both operands must be below `m`.
+  fn mul_mod(&self, other: &Self, m: &Self) -> Self;
+}
+
+macro_rules! impl_number {
+  ($t:ty, $mul_mod:expr) => {
+    impl Number for $t {
+      fn from_usize(n: usize) -> Self {
+        n as $t
+      }
+
+      fn to_usize(&self) -> Option<usize> {
+        usize::try_from(*self).ok()
+      }
+
+      fn to_f64(&self) -> f64 {
+        *self as f64
+      }
+
+      fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(s.parse()?)
+      }
+
+      fn isqrt(&self) -> Self {
+        <$t>::isqrt(*self)
+      }
+
+      fn checked_mul(&self, other: &Self) -> Option<Self> {
+        <$t>::checked_mul(*self, *other)
+      }
+
+      fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+        $mul_mod(*self, *other, *m)
+      }
+    }
+  };
+}
+
+/// Greatest common divisor of `a` and `b`, `a` when `b` is 0.
+pub fn gcd<N: Number>(mut a: N, mut b: N) -> N {
+  let zero = N::from_usize(0);
+  while b != zero {
+    let r = a % b.clone();
+    a = b;
+    b = r;
+  }
+
+  a
+}
+
+impl_number!(usize, |a: usize, b: usize, m: usize| {
+  (a as u128 * b as u128 % m as u128) as usize
+});
+impl_number!(u64, |a: u64, b: u64, m: u64| {
+  (a as u128 * b as u128 % m as u128) as u64
+});
+impl_number!(u128, mul_mod_u128);
+
+fn mul_mod_u128(a: u128, b: u128, m: u128) -> u128 {
+  if let (Ok(a), Ok(b)) = (u64::try_from(a), u64::try_from(b)) {
+    return a as u128 * b as u128 % m;
+  }
+
+  // Double and add, so no intermediate value exceeds m.
+  let mut a = a;
+  let mut b = b;
+  let mut r = 0;
+  while b > 0 {
+    if b & 1 == 1 {
+      r = if r >= m - a { r - (m - a) } else { r + a };
+    }
+    a = if a >= m - a { a - (m - a) } else { a + a };
+    b >>= 1;
+  }
+
+  r
+}
+
+#[cfg(feature = "bigint")]
+impl Number for num_bigint::BigUint {
+  fn from_usize(n: usize) -> Self {
+    Self::from(n)
+  }
+
+  fn to_usize(&self) -> Option<usize> {
+    usize::try_from(self).ok()
+  }
+
+  fn to_f64(&self) -> f64 {
+    num_traits::ToPrimitive::to_f64(self).unwrap_or(f64::INFINITY)
+  }
+
+  fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    Ok(s.parse()?)
+  }
+
+  fn isqrt(&self) -> Self {
+    self.sqrt()
+  }
+
+  fn checked_mul(&self, other: &Self) -> Option<Self> {
+    Some(self * other)
+  }
+
+  fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+    self * other % m
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_mul_mod() {
+    let m = u128::MAX - 158; // largest prime below 2^128
+    assert_eq!(Number::mul_mod(&(m - 1), &(m - 1), &m), 1);
+    assert_eq!(Number::mul_mod(&(m - 1), &2, &m), m - 2);
+    assert_eq!(Number::mul_mod(&(1u128 << 64), &(1u128 << 64), &m), 159);
+    assert_eq!(
+      Number::mul_mod(&(u64::MAX - 1), &(u64::MAX - 1), &u64::MAX),
+      1
+    );
+  }
+
+  #[test]
+  fn test_gcd() {
+    assert_eq!(gcd(84usize, 36), 12);
+    assert_eq!(gcd(7usize, 0), 7);
+    assert_eq!(gcd(0u128, 0), 0);
+  }
+
+  #[test]
+  fn test_isqrt() {
+    assert_eq!(Number::isqrt(&(u128::MAX)), u64::MAX as u128);
+    assert_eq!(
+      Number::isqrt(&1_000_000_000_000_000_000_000u128),
+      31_622_776_601
+    );
+  }
+}
@@ -0,0 +1,195 @@
+use crate::ulam::generator::generator::Generator;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Figurate {
+  /// s-gonal numbers ((s - 2)k^2 - (s - 4)k) / 2.
+  Polygonal(usize),
+  /// Centred s-gonal numbers s k(k + 1) / 2 + 1.
+  Centered(usize),
+  /// Pronic numbers k(k + 1).
+  Pronic,
+}
+
+impl Figurate {
+  /// Whether `n` is a number of this kind, solving its quadratic for k.
+  pub fn contains(&self, n: usize) -> bool {
+    let n = n as u128;
+    match *self {
+      Figurate::Polygonal(s) => {
+        // k = ((s - 4) + x) / 2(s - 2) with x^2 = 8(s - 2)n + (s - 4)^2.
+        let s = s as u128;
+        if n == 0 {
+          return true;
+        }
+
+        match square_root(8 * (s - 2) * n + s.abs_diff(4).pow(2)) {
+          Some(x) => (x + s - 4).is_multiple_of(2 * (s - 2)),
+          None => false,
+        }
+      }
+      Figurate::Centered(s) => {
+        // n - 1 = s T(k) for a triangular number T(k).
+        let s = s as u128;
+        n >= 1 && (n - 1).is_multiple_of(s) && square_root(8 * ((n - 1) / s) + 1).is_some()
+      }
+      Figurate::Pronic => square_root(4 * n + 1).is_some(),
+    }
+  }
+}
+
+fn square_root(m: u128) -> Option<u128> {
+  let r = m.isqrt();
+  if r * r == m {
+    Some(r)
+  } else {
+    None
+  }
+}
+
+/// Marks polygonal, centred polygonal or pronic numbers. Membership is decided
+/// for each n on its own, so no memory grows with the window.
+pub struct PolygonalGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  figurate: Figurate,
+}
+
+impl PolygonalGenerator {
+  pub fn new(n: usize, skip: usize, figurate: Figurate) -> Self {
+    PolygonalGenerator {
+      i: skip,
+      max: n,
+      skip,
+      figurate,
+    }
+  }
+
+  /// `gp` is `from:to:s`, with the number of sides s 3 by default.
+  pub fn from_gp(
+    gp: &str,
+    figurate: fn(usize) -> Figurate,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let (from, to) = parse_range(gp)?;
+    let sides: usize = if let Some(sides) = gp.split(":").nth(2) {
+      sides.parse()?
+    } else {
+      3
+    };
+
+    if sides < 3 {
+      return Err("a polygon needs at least 3 sides".into());
+    }
+
+    Ok(Self::new(to, from, figurate(sides)))
+  }
+
+  /// `gp` is `from:to`, for numbers whose sides are fixed, such as the
+  /// triangular or pronic ones.
+  pub fn fixed_from_gp(gp: &str, figurate: Figurate) -> Result<Self, Box<dyn std::error::Error>> {
+    if let Some(sides) = gp.split(":").nth(2) {
+      return Err(format!("The number of sides is fixed({})", sides).into());
+    }
+
+    let (from, to) = parse_range(gp)?;
+    Ok(Self::new(to, from, figurate))
+  }
+}
+
+fn parse_range(gp: &str) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+  let mut gp = gp.split(":");
+  let from = gp.next();
+  let to = gp.next();
+
+  let from: usize = if let Some(from) = from {
+    from.parse()?
+  } else {
+    1
+  };
+
+  let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+  Ok((from, to))
+}
+
+impl Generator for PolygonalGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    Some((i, self.figurate.contains(i)))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    let name = match self.figurate {
+      Figurate::Polygonal(s) => format!("polygonal, sides = {}", s),
+      Figurate::Centered(s) => format!("centered, sides = {}", s),
+      Figurate::Pronic => "pronic".to_string(),
+    };
+    format!(
+      "generator = {}, from = {}, to = {}",
+      name, self.skip, self.max
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn members(figurate: Figurate, n: usize) -> Vec<usize> {
+    (0..=n).filter(|&i| figurate.contains(i)).collect()
+  }
+
+  #[test]
+  fn test_contains() {
+    assert_eq!(
+      members(Figurate::Polygonal(3), 50),
+      vec![0, 1, 3, 6, 10, 15, 21, 28, 36, 45]
+    );
+    assert_eq!(
+      members(Figurate::Polygonal(4), 50),
+      vec![0, 1, 4, 9, 16, 25, 36, 49]
+    );
+    assert_eq!(
+      members(Figurate::Polygonal(5), 100),
+      vec![0, 1, 5, 12, 22, 35, 51, 70, 92]
+    );
+    assert_eq!(
+      members(Figurate::Polygonal(6), 100),
+      vec![0, 1, 6, 15, 28, 45, 66, 91]
+    );
+    assert_eq!(
+      members(Figurate::Centered(6), 200),
+      vec![1, 7, 19, 37, 61, 91, 127, 169]
+    );
+    assert_eq!(
+      members(Figurate::Centered(3), 50),
+      vec![1, 4, 10, 19, 31, 46]
+    );
+    assert_eq!(
+      members(Figurate::Pronic, 60),
+      vec![0, 2, 6, 12, 20, 30, 42, 56]
+    );
+  }
+
+  #[test]
+  fn test_generate() {
+    let mut ite = PolygonalGenerator::from_gp("4:7:3", Figurate::Polygonal).unwrap();
+    assert_eq!(ite.next(), Some((4, false)));
+    assert_eq!(ite.next(), Some((5, false)));
+    assert_eq!(ite.next(), Some((6, true)));
+    assert_eq!(ite.next(), Some((7, false)));
+    assert_eq!(ite.next(), None);
+    assert!(PolygonalGenerator::from_gp("4:7:2", Figurate::Centered).is_err());
+    assert!(PolygonalGenerator::fixed_from_gp("4:7", Figurate::Pronic).is_ok());
+    assert!(PolygonalGenerator::fixed_from_gp("4:7:2", Figurate::Polygonal(3)).is_err());
+  }
+}
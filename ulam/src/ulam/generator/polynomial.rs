@@ -0,0 +1,258 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::primality::is_prime;
+use std::fmt;
+use std::str::FromStr;
+
+// Above this, f(n) overflows an i128 for every n > 1.
+const MAX_DEGREE: usize = 127;
+
+/// Integer polynomial in n such as `n^2+n+41` or `4n^2 - 2*n + 41`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Polynomial {
+  // Coefficient of n^k at index k.
+  coefficients: Vec<i128>,
+}
+
+impl Polynomial {
+  /// f(n), or `None` when it doesn't fit in an i128.
+  pub fn eval(&self, n: usize) -> Option<i128> {
+    let n = n as i128;
+    self
+      .coefficients
+      .iter()
+      .rev()
+      .try_fold(0i128, |acc, &c| acc.checked_mul(n)?.checked_add(c))
+  }
+}
+
+impl FromStr for Polynomial {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || format!("Invalid polynomial({})", s);
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if s.is_empty() {
+      return Err(invalid());
+    }
+
+    // Split before every sign that isn't leading, so each term keeps its sign.
+    let mut terms = vec![];
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+      if (c == '+' || c == '-') && i > 0 {
+        terms.push(&s[start..i]);
+        start = i;
+      }
+    }
+    terms.push(&s[start..]);
+
+    let mut coefficients = vec![];
+    for term in terms {
+      let (negative, term) = match term.as_bytes()[0] {
+        b'-' => (true, &term[1..]),
+        b'+' => (false, &term[1..]),
+        _ => (false, term),
+      };
+
+      let (coefficient, degree) = if let Some(i) = term.find('n') {
+        let coefficient = term[..i].strip_suffix('*').unwrap_or(&term[..i]);
+        let coefficient: i128 = if coefficient.is_empty() {
+          1
+        } else {
+          coefficient.parse().map_err(|_| invalid())?
+        };
+
+        let power = &term[i + 1..];
+        let degree: usize = if power.is_empty() {
+          1
+        } else if let Some(power) = power.strip_prefix('^') {
+          power.parse().map_err(|_| invalid())?
+        } else {
+          return Err(invalid());
+        };
+
+        if degree > MAX_DEGREE {
+          return Err(format!("Degree must be at most {}({})", MAX_DEGREE, s));
+        }
+
+        (coefficient, degree)
+      } else {
+        (term.parse().map_err(|_| invalid())?, 0)
+      };
+
+      if coefficients.len() <= degree {
+        coefficients.resize(degree + 1, 0);
+      }
+      let coefficient = if negative { -coefficient } else { coefficient };
+      coefficients[degree] += coefficient;
+    }
+
+    while coefficients.len() > 1 && coefficients[coefficients.len() - 1] == 0 {
+      coefficients.pop();
+    }
+
+    Ok(Polynomial { coefficients })
+  }
+}
+
+impl fmt::Display for Polynomial {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut first = true;
+    for (k, &c) in self.coefficients.iter().enumerate().rev() {
+      if c == 0 && !(k == 0 && first) {
+        continue;
+      }
+
+      if first {
+        if c < 0 {
+          write!(f, "-")?;
+        }
+      } else {
+        write!(f, "{}", if c < 0 { "-" } else { "+" })?;
+      }
+      first = false;
+
+      let c = c.unsigned_abs();
+      if c != 1 || k == 0 {
+        write!(f, "{}", c)?;
+      }
+      match k {
+        0 => {}
+        1 => write!(f, "n")?,
+        _ => write!(f, "n^{}", k)?,
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Marks n when |f(n)| is prime for an integer polynomial f. Values beyond
+/// an i128 are never marked.
+pub struct PolynomialGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  polynomial: Polynomial,
+  hits: usize,
+}
+
+impl PolynomialGenerator {
+  pub fn new(n: usize, skip: usize, polynomial: Polynomial) -> Self {
+    PolynomialGenerator {
+      i: skip,
+      max: n,
+      skip,
+      polynomial,
+      hits: 0,
+    }
+  }
+
+  pub fn from_gp(gp: &str, polynomial: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    Ok(Self::new(to, from, polynomial.parse()?))
+  }
+
+  fn is_hit(&self, n: usize) -> bool {
+    match self.polynomial.eval(n) {
+      Some(v) => is_prime(&v.unsigned_abs()),
+      None => false,
+    }
+  }
+}
+
+impl Generator for PolynomialGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    let hit = self.is_hit(i);
+    self.hits += hit as usize;
+    Some((i, hit))
+  }
+
+  // The share of primes is of the numbers yielded so far.
+  fn generator_info(&self) -> std::string::String {
+    let total = self.i - self.skip;
+    let hits = self.hits;
+    format!(
+      "generator = {}, from = {}, to = {}, primes = {:.1}%",
+      self.polynomial,
+      self.skip,
+      self.max,
+      hits as f64 * 100.0 / total.max(1) as f64
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    let f: Polynomial = "n^2+n+41".parse().unwrap();
+    assert_eq!(f.coefficients, vec![41, 1, 1]);
+    assert_eq!(f.to_string(), "n^2+n+41");
+
+    let f: Polynomial = " -4n^2 - 2*n + 3n^2 +n^2 - 7 ".parse().unwrap();
+    assert_eq!(f.coefficients, vec![-7, -2]);
+    assert_eq!(f.to_string(), "-2n-7");
+
+    assert_eq!("0".parse::<Polynomial>().unwrap().to_string(), "0");
+    assert!("n^".parse::<Polynomial>().is_err());
+    assert!("2m+1".parse::<Polynomial>().is_err());
+    assert!("".parse::<Polynomial>().is_err());
+    assert!("n^127".parse::<Polynomial>().is_ok());
+    assert!("n^4000000000".parse::<Polynomial>().is_err());
+  }
+
+  #[test]
+  fn test_eval() {
+    let f: Polynomial = "n^2-79n+1601".parse().unwrap();
+    assert_eq!(f.eval(40), Some(41));
+    assert_eq!(f.eval(80), Some(1681));
+    let f: Polynomial = "n^5".parse().unwrap();
+    assert_eq!(f.eval(usize::MAX), None);
+  }
+
+  #[test]
+  fn test_generate() {
+    let mut ite = PolynomialGenerator::from_gp("38:41", "n^2+n+41").unwrap();
+    assert_eq!(
+      ite.generator_info(),
+      "generator = n^2+n+41, from = 38, to = 41, primes = 0.0%"
+    );
+    assert_eq!(ite.next(), Some((38, true)));
+    assert_eq!(ite.next(), Some((39, true)));
+    assert_eq!(ite.next(), Some((40, false)));
+    assert_eq!(ite.next(), Some((41, false)));
+    assert_eq!(ite.next(), None);
+    assert_eq!(
+      ite.generator_info(),
+      "generator = n^2+n+41, from = 38, to = 41, primes = 50.0%"
+    );
+
+    // |f(n)| is tested, so negative values count.
+    let mut ite = PolynomialGenerator::new(1, 1, "n-6".parse().unwrap());
+    assert_eq!(ite.next(), Some((1, true)));
+  }
+}
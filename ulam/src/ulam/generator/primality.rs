@@ -0,0 +1,400 @@
+use crate::ulam::generator::number::Number;
+
+const SMALL_PRIMES: [usize; 25] = [
+  2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+// Miller–Rabin with the first 13 primes as bases is exact below this bound,
+// which covers every 64-bit integer.
+const MILLER_RABIN_BOUND: f64 = 3.3e24;
+
+/// Tells whether `n` is prime.
+///
+/// Deterministic Miller–Rabin is used below 3.3 * 10^24. Beyond that this is
+/// the Baillie–PSW test, for which no counterexample is known.
+pub fn is_prime<N: Number>(n: &N) -> bool {
+  let zero = N::from_usize(0);
+  for &p in SMALL_PRIMES.iter() {
+    let p = N::from_usize(p);
+    if *n == p {
+      return true;
+    }
+
+    if n.clone() % p == zero {
+      return false;
+    }
+  }
+
+  if *n < N::from_usize(2) {
+    return false;
+  }
+
+  if n.to_f64() < MILLER_RABIN_BOUND {
+    SMALL_PRIMES[..13]
+      .iter()
+      .all(|&a| strong_probable_prime(n, &N::from_usize(a)))
+  } else {
+    strong_probable_prime(n, &N::from_usize(2)) && strong_lucas_probable_prime(n)
+  }
+}
+
+/// Tells whether the Gaussian integer x + yi is prime: either its norm
+/// x^2 + y^2 is prime, or it is a rational prime 3 mod 4 times a unit.
+pub fn is_gaussian_prime(x: isize, y: isize) -> bool {
+  let (x, y) = (x.unsigned_abs() as u128, y.unsigned_abs() as u128);
+  if x == 0 || y == 0 {
+    let m = x + y;
+    m % 4 == 3 && is_prime(&m)
+  } else {
+    is_prime(&(x * x + y * y))
+  }
+}
+
+/// Tells whether the Eisenstein integer a + bω is prime: either its norm
+/// a^2 - ab + b^2 is prime, or it is a rational prime 2 mod 3 times a unit.
+pub fn is_eisenstein_prime(a: isize, b: isize) -> bool {
+  if a == 0 || b == 0 || a == b {
+    let m = a.unsigned_abs().max(b.unsigned_abs()) as u128;
+    m % 3 == 2 && is_prime(&m)
+  } else {
+    let (a, b) = (a as i128, b as i128);
+    is_prime(&((a * a - a * b + b * b) as u128))
+  }
+}
+
+fn pow_mod<N: Number>(base: &N, exp: &N, m: &N) -> N {
+  let zero = N::from_usize(0);
+  let two = N::from_usize(2);
+  let mut base = base.clone() % m.clone();
+  let mut exp = exp.clone();
+  let mut r = N::from_usize(1) % m.clone();
+  while exp > zero {
+    if exp.clone() % two.clone() != zero {
+      r = r.mul_mod(&base, m);
+    }
+    base = base.mul_mod(&base, m);
+    exp = exp / two.clone();
+  }
+
+  r
+}
+
+fn add_mod<N: Number>(a: &N, b: &N, m: &N) -> N {
+  let rest = m.clone() - b.clone();
+  if *a >= rest {
+    a.clone() - rest
+  } else {
+    a.clone() + b.clone()
+  }
+}
+
+fn sub_mod<N: Number>(a: &N, b: &N, m: &N) -> N {
+  if a >= b {
+    a.clone() - b.clone()
+  } else {
+    m.clone() - (b.clone() - a.clone())
+  }
+}
+
+// a / 2 modulo an odd m.
+fn half_mod<N: Number>(a: &N, m: &N) -> N {
+  let two = N::from_usize(2);
+  if a.clone() % two.clone() == N::from_usize(0) {
+    a.clone() / two
+  } else {
+    a.clone() / two.clone() + m.clone() / two + N::from_usize(1)
+  }
+}
+
+// d modulo n for a signed d.
+fn signed_mod<N: Number>(d: i64, n: &N) -> N {
+  let r = N::from_usize(d.unsigned_abs() as usize) % n.clone();
+  if d >= 0 {
+    r
+  } else {
+    sub_mod(&N::from_usize(0), &r, n)
+  }
+}
+
+/// Tells whether `n` passes Fermat's test to `base`, base^(n-1) = 1 mod n.
+/// `n` must be at least 2.
+pub fn fermat_probable_prime<N: Number>(n: &N, base: &N) -> bool {
+  let one = N::from_usize(1);
+  pow_mod(base, &(n.clone() - one.clone()), n) == one
+}
+
+/// Tells whether `n` passes the Euler–Jacobi test to `base`,
+/// base^((n-1)/2) = (base/n) mod n with a non-zero Jacobi symbol.
+/// `n` must be odd and at least 3.
+pub fn euler_probable_prime<N: Number>(n: &N, base: &N) -> bool {
+  let one = N::from_usize(1);
+  let x = pow_mod(base, &((n.clone() - one.clone()) / N::from_usize(2)), n);
+  match jacobi(base, n) {
+    1 => x == one,
+    -1 => x == n.clone() - one,
+    _ => false,
+  }
+}
+
+/// Tells whether `n` passes the Miller–Rabin test to `base`.
+/// `n` must be odd and at least 3.
+pub fn strong_probable_prime<N: Number>(n: &N, base: &N) -> bool {
+  let one = N::from_usize(1);
+  let two = N::from_usize(2);
+  let n_1 = n.clone() - one.clone();
+
+  let mut d = n_1.clone();
+  let mut s = 0;
+  while d.clone() % two.clone() == N::from_usize(0) {
+    d = d / two.clone();
+    s += 1;
+  }
+
+  let mut x = pow_mod(base, &d, n);
+  if x == one || x == n_1 {
+    return true;
+  }
+
+  for _ in 1..s {
+    x = x.mul_mod(&x, n);
+    if x == n_1 {
+      return true;
+    }
+    if x == one {
+      return false;
+    }
+  }
+
+  false
+}
+
+fn jacobi<N: Number>(a: &N, n: &N) -> i32 {
+  let zero = N::from_usize(0);
+  let two = N::from_usize(2);
+  let rem = |x: &N, m: usize| (x.clone() % N::from_usize(m)).to_usize().unwrap();
+
+  let mut a = a.clone() % n.clone();
+  let mut n = n.clone();
+  let mut t = 1;
+  while a != zero {
+    while a.clone() % two.clone() == zero {
+      a = a / two.clone();
+      let r = rem(&n, 8);
+      if r == 3 || r == 5 {
+        t = -t;
+      }
+    }
+
+    std::mem::swap(&mut a, &mut n);
+    if rem(&a, 4) == 3 && rem(&n, 4) == 3 {
+      t = -t;
+    }
+    a = a % n.clone();
+  }
+
+  if n == N::from_usize(1) {
+    t
+  } else {
+    0
+  }
+}
+
+// Strong Lucas test with Selfridge's parameters (P = 1, Q = (1 - D) / 4).
+// `n` must be odd and free of the small prime factors.
+fn strong_lucas_probable_prime<N: Number>(n: &N) -> bool {
+  let zero = N::from_usize(0);
+  let two = N::from_usize(2);
+
+  let root = n.isqrt();
+  if root.clone() * root == *n {
+    return false;
+  }
+
+  let mut d: i64 = 5;
+  loop {
+    match jacobi(&signed_mod(d, n), n) {
+      -1 => break,
+      0 => return false,
+      _ => d = if d > 0 { -(d + 2) } else { -d + 2 },
+    }
+  }
+
+  let dm = signed_mod(d, n);
+  let q = signed_mod((1 - d) / 4, n);
+
+  let mut k = n.clone() + N::from_usize(1);
+  let mut s = 0;
+  while k.clone() % two.clone() == zero {
+    k = k / two.clone();
+    s += 1;
+  }
+
+  let mut bits = vec![];
+  while k > zero {
+    bits.push(k.clone() % two.clone() != zero);
+    k = k / two.clone();
+  }
+
+  let mut u = N::from_usize(1);
+  let mut v = N::from_usize(1);
+  let mut qk = q.clone();
+  for &bit in bits.iter().rev().skip(1) {
+    u = u.mul_mod(&v, n);
+    v = sub_mod(&v.mul_mod(&v, n), &add_mod(&qk, &qk, n), n);
+    qk = qk.mul_mod(&qk, n);
+    if bit {
+      let u1 = half_mod(&add_mod(&u, &v, n), n);
+      let v1 = half_mod(&add_mod(&dm.mul_mod(&u, n), &v, n), n);
+      u = u1;
+      v = v1;
+      qk = qk.mul_mod(&q, n);
+    }
+  }
+
+  if u == zero || v == zero {
+    return true;
+  }
+
+  for _ in 1..s {
+    v = sub_mod(&v.mul_mod(&v, n), &add_mod(&qk, &qk, n), n);
+    qk = qk.mul_mod(&qk, n);
+    if v == zero {
+      return true;
+    }
+  }
+
+  false
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn is_prime_naive(n: usize) -> bool {
+    n >= 2
+      && (2..)
+        .take_while(|d| d * d <= n)
+        .all(|d| !n.is_multiple_of(d))
+  }
+
+  #[test]
+  fn test_is_prime_small() {
+    for n in 0..10000usize {
+      assert_eq!(is_prime(&n), is_prime_naive(n), "n = {}", n);
+    }
+  }
+
+  #[test]
+  fn test_is_prime_pseudoprimes() {
+    // Strong pseudoprimes to several bases and Carmichael numbers.
+    for &n in &[
+      2047u128,
+      3215031751,
+      3825123056546413051,
+      318665857834031151167461,
+      561,
+      41041,
+    ] {
+      assert!(!is_prime(&n), "n = {}", n);
+    }
+  }
+
+  #[test]
+  fn test_is_prime_large() {
+    assert!(is_prime(&(u64::MAX - 58)));
+    assert!(!is_prime(&(u64::MAX - 56)));
+    assert!(is_prime(&((1u128 << 89) - 1)));
+    assert!(is_prime(&((1u128 << 127) - 1)));
+    assert!(is_prime(&(u128::MAX - 158)));
+    assert!(!is_prime(&((1u128 << 67) - 1)));
+    assert!(!is_prime(&(((1u128 << 61) - 1) * (u64::MAX as u128 - 58))));
+  }
+
+  #[cfg(feature = "bigint")]
+  #[test]
+  fn test_is_prime_big() {
+    let one = num_bigint::BigUint::from(1u32);
+    assert!(is_prime(&((one.clone() << 521) - one.clone())));
+    assert!(!is_prime(&((one.clone() << 523) - one)));
+  }
+
+  #[test]
+  fn test_probable_prime() {
+    // 341 = 11 * 31 fools Fermat's test to base 2 only, 561 also Euler's,
+    // and 2047 = 23 * 89 all three.
+    assert!(fermat_probable_prime(&341usize, &2));
+    assert!(!euler_probable_prime(&341usize, &2));
+    assert!(euler_probable_prime(&561usize, &2));
+    assert!(!strong_probable_prime(&561usize, &2));
+    assert!(strong_probable_prime(&2047usize, &2));
+    assert!(euler_probable_prime(&2047usize, &2));
+    assert!(!fermat_probable_prime(&341usize, &3));
+  }
+
+  #[test]
+  fn test_is_gaussian_prime() {
+    let primes: Vec<(isize, isize)> = (0..6)
+      .flat_map(|x| (0..6).map(move |y| (x, y)))
+      .filter(|&(x, y)| is_gaussian_prime(x, y))
+      .collect();
+    assert_eq!(
+      primes,
+      vec![
+        (0, 3),
+        (1, 1),
+        (1, 2),
+        (1, 4),
+        (2, 1),
+        (2, 3),
+        (2, 5),
+        (3, 0),
+        (3, 2),
+        (4, 1),
+        (4, 5),
+        (5, 2),
+        (5, 4)
+      ]
+    );
+    assert!(is_gaussian_prime(-7, 0));
+    assert!(is_gaussian_prime(-1, -2));
+    assert!(!is_gaussian_prime(-5, 0));
+  }
+
+  #[test]
+  fn test_is_eisenstein_prime() {
+    // 1 - ω has norm 3 and 2 stays prime; 3 = -ω^2(1 - ω)^2 and 7 split.
+    assert!(is_eisenstein_prime(1, -1));
+    assert!(is_eisenstein_prime(2, 0));
+    assert!(is_eisenstein_prime(0, -5));
+    assert!(is_eisenstein_prime(-2, -2));
+    assert!(!is_eisenstein_prime(3, 0));
+    assert!(!is_eisenstein_prime(7, 0));
+    assert!(is_eisenstein_prime(3, 1));
+    assert!(!is_eisenstein_prime(1, 0));
+    assert!(!is_eisenstein_prime(0, 0));
+    // Up to norm 100: 6 associates each of 1 - ω, 2 and 5, and 12 for each
+    // of the 11 primes 1 mod 3.
+    let count = (-12..=12isize)
+      .flat_map(|a| (-12..=12).map(move |b| (a, b)))
+      .filter(|&(a, b)| a * a - a * b + b * b <= 100 && is_eisenstein_prime(a, b))
+      .count();
+    assert_eq!(count, 150);
+  }
+
+  #[test]
+  fn test_strong_lucas() {
+    // The smallest strong Lucas pseudoprimes pass the Lucas half alone.
+    assert!(strong_lucas_probable_prime(&5459u128));
+    assert!(strong_lucas_probable_prime(&5777u128));
+    for n in (101..5459u128).step_by(2) {
+      if n % 3 != 0 && n % 5 != 0 && n % 7 != 0 {
+        assert_eq!(
+          strong_lucas_probable_prime(&n),
+          is_prime_naive(n as usize),
+          "n = {}",
+          n
+        );
+      }
+    }
+  }
+}
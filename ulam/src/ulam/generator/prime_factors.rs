@@ -0,0 +1,158 @@
+use crate::ulam::generator::factors::FactorSieve;
+use crate::ulam::generator::generator::Generator;
+
+/// Categorizes n by Ω(n), the number of prime factors counted with
+/// multiplicity. Primes are category 1.
+pub struct PrimeFactorsGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  sieve: FactorSieve,
+  // Ω of each number of the window when drawn as a heat map, kept from the
+  // pass that finds their range.
+  values: Option<Vec<u8>>,
+}
+
+impl PrimeFactorsGenerator {
+  pub fn new(n: usize, skip: usize) -> Self {
+    PrimeFactorsGenerator {
+      i: skip,
+      max: n,
+      skip,
+      sieve: FactorSieve::new(n),
+      values: None,
+    }
+  }
+
+  /// Yields Ω(n) as a value, so it is drawn as a heat map. The window is
+  /// factorized here, once, for both the value range and the values.
+  pub fn with_values(mut self) -> Self {
+    let values = (self.skip..=self.max)
+      .map(|n| self.omega(n) as u8)
+      .collect();
+    self.values = Some(values);
+    self
+  }
+
+  pub fn from_gp(gp: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+    FactorSieve::check(to)?;
+
+    Ok(Self::new(to, from))
+  }
+
+  fn omega(&mut self, n: usize) -> usize {
+    self
+      .sieve
+      .factorize(n)
+      .iter()
+      .map(|&(_, e)| e as usize)
+      .sum()
+  }
+}
+
+impl Generator for PrimeFactorsGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    // No n up to max has more than log2(max) prime factors.
+    let max_omega = (usize::BITS - self.max.max(1).leading_zeros() - 1) as usize;
+    (0..=max_omega).map(|k| format!("Ω = {}", k)).collect()
+  }
+
+  fn next_category(&mut self) -> Option<(usize, usize)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    let omega = match &self.values {
+      Some(values) => values[i - self.skip] as usize,
+      None => self.omega(i),
+    };
+    Some((i, omega))
+  }
+
+  fn value_range(&self) -> Option<(f64, f64)> {
+    // 0 is left blank, so its value is not in the range.
+    let values = self.values.as_ref()?;
+    let values = values.iter().skip((self.skip == 0) as usize);
+    let min = *values.clone().min()?;
+    let max = *values.max()?;
+    Some((min as f64, max as f64))
+  }
+
+  fn next_value(&mut self) -> Option<(usize, Option<f64>)> {
+    self
+      .next_category()
+      .map(|(n, c)| (n, if n != 0 { Some(c as f64) } else { None }))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = {}, from = {}, to = {}",
+      if self.values.is_some() {
+        "bigomega"
+      } else {
+        "factors"
+      },
+      self.skip,
+      self.max
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let mut ite = PrimeFactorsGenerator::new(12, 7);
+    assert_eq!(ite.next_category(), Some((7, 1)));
+    assert_eq!(ite.next_category(), Some((8, 3)));
+    assert_eq!(ite.next_category(), Some((9, 2)));
+    assert_eq!(ite.next_category(), Some((10, 2)));
+    assert_eq!(ite.next_category(), Some((11, 1)));
+    assert_eq!(ite.next_category(), Some((12, 3)));
+    assert_eq!(ite.next_category(), None);
+    assert_eq!(ite.categories().len(), 4);
+  }
+
+  #[test]
+  fn test_values() {
+    assert_eq!(PrimeFactorsGenerator::new(12, 0).value_range(), None);
+
+    let mut ite = PrimeFactorsGenerator::new(12, 0).with_values();
+    assert_eq!(ite.next_value(), Some((0, None)));
+    assert_eq!(ite.next_value(), Some((1, Some(0.0))));
+    assert_eq!(ite.next_value(), Some((2, Some(1.0))));
+    assert_eq!(ite.value_range(), Some((0.0, 3.0)));
+
+    let mut ite = PrimeFactorsGenerator::new(12, 7).with_values();
+    assert_eq!(ite.value_range(), Some((1.0, 3.0)));
+    assert_eq!(ite.next_value(), Some((7, Some(1.0))));
+    assert_eq!(ite.next_value(), Some((8, Some(3.0))));
+    assert_eq!(
+      ite.generator_info(),
+      "generator = bigomega, from = 7, to = 12"
+    );
+  }
+}
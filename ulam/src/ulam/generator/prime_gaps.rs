@@ -0,0 +1,113 @@
+use crate::ulam::generator::generator::scan_value_range;
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::sieve::SegmentedSieve;
+
+/// Yields, for each prime p, the gap to the next prime as the value of p.
+/// Other numbers are left blank.
+pub struct PrimeGapsGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  next_prime: usize,
+  sieve: SegmentedSieve,
+}
+
+impl PrimeGapsGenerator {
+  pub fn new(n: usize, skip: usize) -> Self {
+    // By Bertrand's postulate the prime after p <= n is below 2n.
+    let mut sieve = SegmentedSieve::new(n.saturating_mul(2).max(3));
+    let next_prime = Self::prime_from(&mut sieve, skip);
+    PrimeGapsGenerator {
+      i: skip,
+      max: n,
+      skip,
+      next_prime,
+      sieve,
+    }
+  }
+
+  pub fn from_gp(gp: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    Ok(Self::new(to, from))
+  }
+
+  fn prime_from(sieve: &mut SegmentedSieve, mut n: usize) -> usize {
+    while !sieve.is_prime(n) {
+      n += 1;
+    }
+
+    n
+  }
+}
+
+impl Generator for PrimeGapsGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_value().map(|(n, v)| (n, v.is_some()))
+  }
+
+  fn value_range(&self) -> Option<(f64, f64)> {
+    scan_value_range(&mut Self::new(self.max, self.skip))
+  }
+
+  fn next_value(&mut self) -> Option<(usize, Option<f64>)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    if i != self.next_prime {
+      return Some((i, None));
+    }
+
+    self.next_prime = Self::prime_from(&mut self.sieve, i + 1);
+    Some((i, Some((self.next_prime - i) as f64)))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!("generator = gaps, from = {}, to = {}", self.skip, self.max)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let mut ite = PrimeGapsGenerator::new(24, 0);
+    let values: Vec<_> = std::iter::from_fn(|| ite.next_value())
+      .filter_map(|(n, v)| v.map(|v| (n, v)))
+      .collect();
+    assert_eq!(
+      values,
+      vec![
+        (2, 1.0),
+        (3, 2.0),
+        (5, 2.0),
+        (7, 4.0),
+        (11, 2.0),
+        (13, 4.0),
+        (17, 2.0),
+        (19, 4.0),
+        (23, 6.0)
+      ]
+    );
+    assert_eq!(ite.value_range(), Some((1.0, 6.0)));
+  }
+}
@@ -1,18 +1,30 @@
 use crate::ulam::generator::generator::Generator;
-use core::iter::Skip;
-
-pub struct PrimesGenerator {
-  max: usize,
-  skip: usize,
-  iter: Skip<PrimesOrNotIterator>,
+use crate::ulam::generator::number::Number;
+use crate::ulam::generator::primality::is_prime;
+use crate::ulam::generator::sieve::SegmentedSieve;
+
+// Windows ending below this are sieved; beyond it the base primes get too
+// large, so each number is tested on its own.
+const SIEVE_LIMIT: usize = 1 << 40;
+
+pub struct PrimesGenerator<N = usize> {
+  max: N,
+  skip: N,
+  iter: PrimesOrNotIterator<N>,
 }
 
 impl PrimesGenerator {
   pub fn new(n: usize, skip: usize) -> Self {
+    Self::with_bounds(n, skip)
+  }
+}
+
+impl<N: Number> PrimesGenerator<N> {
+  pub fn with_bounds(n: N, skip: N) -> Self {
     PrimesGenerator {
-      max: n,
-      skip,
-      iter: PrimesOrNotIterator::new(n).skip(skip),
+      max: n.clone(),
+      skip: skip.clone(),
+      iter: PrimesOrNotIterator::new(n, skip),
     }
   }
 
@@ -21,36 +33,38 @@ impl PrimesGenerator {
     let from = gp.next();
     let to = gp.next();
 
-    let from: usize = if let Some(from) = from {
-      from.parse()?
+    let from: N = if let Some(from) = from {
+      N::parse(from)?
     } else {
-      1
+      N::from_usize(1)
     };
 
-    let to: usize = if let Some(to) = to {
-      to.parse()?
+    let to: N = if let Some(to) = to {
+      N::parse(to)?
     } else {
-      1000
+      N::from_usize(1000)
     };
 
-    Ok(Self::new(to, from))
+    Ok(Self::with_bounds(to, from))
   }
 
-  pub fn max(&self) -> usize {
-    self.max
+  pub fn max(&self) -> N {
+    self.max.clone()
   }
 
-  pub fn skip(&self) -> usize {
-    self.skip
+  pub fn skip(&self) -> N {
+    self.skip.clone()
   }
 }
 
-impl Generator for PrimesGenerator {
+impl<N: Number> Generator<N> for PrimesGenerator<N> {
   fn data_num(&self) -> usize {
-    self.max - self.skip
+    (self.max.clone() - self.skip.clone())
+      .to_usize()
+      .unwrap_or(usize::MAX)
   }
 
-  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+  fn next(&mut self) -> std::option::Option<(N, bool)> {
     self.iter.next()
   }
 
@@ -62,51 +76,42 @@ impl Generator for PrimesGenerator {
   }
 }
 
-pub struct PrimesOrNotIterator {
-  i: usize,
-  max: usize,
-  primes: Vec<usize>,
+pub struct PrimesOrNotIterator<N = usize> {
+  i: N,
+  max: N,
+  sieve: Option<SegmentedSieve>,
 }
 
-impl PrimesOrNotIterator {
-  pub fn new(n: usize) -> PrimesOrNotIterator {
+impl<N: Number> PrimesOrNotIterator<N> {
+  pub fn new(n: N, from: N) -> PrimesOrNotIterator<N> {
+    let sieve = match n.to_usize() {
+      Some(max) if max <= SIEVE_LIMIT => Some(SegmentedSieve::new(max)),
+      _ => None,
+    };
+
     PrimesOrNotIterator {
-      i: 0,
+      i: from,
       max: n,
-      primes: vec![],
+      sieve,
     }
   }
 }
 
-impl Iterator for PrimesOrNotIterator {
-  type Item = (usize, bool);
+impl<N: Number> Iterator for PrimesOrNotIterator<N> {
+  type Item = (N, bool);
 
   fn next(&mut self) -> Option<Self::Item> {
-    let i = self.i;
+    let i = self.i.clone();
     if self.i > self.max {
       return None;
     }
 
-    if self.i < 2 {
-      self.i += 1;
-      return Some((i, false));
-    }
-
-    let mut n = i;
-    for &p in &self.primes {
-      while n % p == 0 {
-        n /= p;
-      }
-    }
-
-    self.i += 1;
-
-    if n != 1 {
-      self.primes.push(i);
-      Some((i, true))
-    } else {
-      Some((i, false))
-    }
+    self.i = i.clone() + N::from_usize(1);
+    let b = match self.sieve {
+      Some(ref mut sieve) => sieve.is_prime(i.to_usize().unwrap()),
+      None => is_prime(&i),
+    };
+    Some((i, b))
   }
 }
 
@@ -125,4 +130,25 @@ mod tests {
     assert_eq!(ite.next(), Some((10, false)));
     assert_eq!(ite.next(), None);
   }
+
+  #[test]
+  fn test_generate_far() {
+    let mut ite = PrimesGenerator::new(1_000_000_009, 1_000_000_006);
+    assert_eq!(ite.next(), Some((1_000_000_006, false)));
+    assert_eq!(ite.next(), Some((1_000_000_007, true)));
+    assert_eq!(ite.next(), Some((1_000_000_008, false)));
+    assert_eq!(ite.next(), Some((1_000_000_009, true)));
+    assert_eq!(ite.next(), None);
+  }
+
+  #[test]
+  fn test_generate_u128() {
+    let mut ite =
+      PrimesGenerator::<u128>::from_gp("100000000000000000000:100000000000000000040").unwrap();
+    let primes: Vec<u128> = std::iter::from_fn(|| ite.next())
+      .filter(|x| x.1)
+      .map(|x| x.0)
+      .collect();
+    assert_eq!(primes, vec![100000000000000000039]);
+  }
 }
@@ -0,0 +1,174 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::gcd;
+use crate::ulam::generator::number::Number;
+use crate::ulam::generator::primes::PrimesGenerator;
+
+/// Marks primes p with p ≡ r (mod m) for r in a set of residues. Each
+/// residue is its own category, so all of them can be told apart in one image.
+pub struct PrimesInClassGenerator<N = usize> {
+  gen: PrimesGenerator<N>,
+  modulus: usize,
+  residues: Vec<usize>,
+}
+
+impl PrimesInClassGenerator {
+  pub fn new(n: usize, skip: usize, modulus: usize, residues: Vec<usize>) -> Self {
+    Self::with_bounds(n, skip, modulus, residues)
+  }
+}
+
+impl<N: Number> PrimesInClassGenerator<N> {
+  pub fn with_bounds(n: N, skip: N, modulus: usize, residues: Vec<usize>) -> Self {
+    Self::with_primes(PrimesGenerator::with_bounds(n, skip), modulus, residues)
+  }
+
+  fn with_primes(gen: PrimesGenerator<N>, modulus: usize, residues: Vec<usize>) -> Self {
+    let mut residues: Vec<usize> = residues.iter().map(|r| r % modulus).collect();
+    residues.sort_unstable();
+    residues.dedup();
+
+    PrimesInClassGenerator {
+      gen,
+      modulus,
+      residues,
+    }
+  }
+
+  /// `residues` is a comma separated list such as `1,5`. When it is omitted
+  /// every residue coprime to `modulus` is used.
+  pub fn from_gp(
+    gp: &str,
+    modulus: usize,
+    residues: Option<&str>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    if modulus == 0 {
+      return Err("modulus must be positive".into());
+    }
+
+    let residues: Vec<usize> = if let Some(residues) = residues {
+      residues
+        .split(",")
+        .map(|r| r.trim().parse())
+        .collect::<Result<_, _>>()?
+    } else {
+      (0..modulus).filter(|&r| gcd(r, modulus) == 1).collect()
+    };
+
+    Ok(Self::with_primes(
+      PrimesGenerator::from_gp(gp)?,
+      modulus,
+      residues,
+    ))
+  }
+}
+
+impl<N: Number> Generator<N> for PrimesInClassGenerator<N> {
+  fn data_num(&self) -> usize {
+    self.gen.data_num()
+  }
+
+  fn next(&mut self) -> std::option::Option<(N, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    let mut categories = vec![String::new()];
+    categories.extend(
+      self
+        .residues
+        .iter()
+        .map(|r| format!("{} mod {}", r, self.modulus)),
+    );
+    categories
+  }
+
+  fn next_category(&mut self) -> Option<(N, usize)> {
+    if let Some(x) = self.gen.next() {
+      if !x.1 {
+        return Some((x.0, 0));
+      }
+
+      let r = (x.0.clone() % N::from_usize(self.modulus))
+        .to_usize()
+        .unwrap();
+      match self.residues.binary_search(&r) {
+        Ok(i) => Some((x.0, i + 1)),
+        Err(_) => Some((x.0, 0)),
+      }
+    } else {
+      None
+    }
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    let residues: Vec<String> = self.residues.iter().map(|r| r.to_string()).collect();
+    format!(
+      "generator = primes, from = {}, to = {}, mod = {}, residues = {}",
+      self.gen.skip(),
+      self.gen.max(),
+      self.modulus,
+      residues.join(",")
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let mut ite = PrimesInClassGenerator::new(11, 7, 10, vec![1]);
+    assert_eq!(ite.next(), Some((7, false)));
+    assert_eq!(ite.next(), Some((8, false)));
+    assert_eq!(ite.next(), Some((9, false)));
+    assert_eq!(ite.next(), Some((10, false)));
+    assert_eq!(ite.next(), Some((11, true)));
+    assert_eq!(ite.next(), None);
+
+    let mut ite = PrimesInClassGenerator::new(5, 2, 10, vec![3]);
+    assert_eq!(ite.next(), Some((2, false)));
+    assert_eq!(ite.next(), Some((3, true)));
+    assert_eq!(ite.next(), Some((4, false)));
+    assert_eq!(ite.next(), Some((5, false)));
+    assert_eq!(ite.next(), None);
+
+    let mut ite = PrimesInClassGenerator::new(8, 4, 10, vec![7]);
+    assert_eq!(ite.next(), Some((4, false)));
+    assert_eq!(ite.next(), Some((5, false)));
+    assert_eq!(ite.next(), Some((6, false)));
+    assert_eq!(ite.next(), Some((7, true)));
+    assert_eq!(ite.next(), Some((8, false)));
+    assert_eq!(ite.next(), None);
+  }
+
+  #[test]
+  fn test_from_gp() {
+    let mut ite = PrimesInClassGenerator::<usize>::from_gp("1:30", 12, Some("1,5")).unwrap();
+    let primes: Vec<usize> = std::iter::from_fn(|| ite.next())
+      .filter(|x| x.1)
+      .map(|x| x.0)
+      .collect();
+    assert_eq!(primes, vec![5, 13, 17, 29]);
+
+    let ite = PrimesInClassGenerator::<usize>::from_gp("1:30", 12, None).unwrap();
+    assert_eq!(ite.residues, vec![1, 5, 7, 11]);
+  }
+
+  #[test]
+  fn test_categories() {
+    let mut ite = PrimesInClassGenerator::new(13, 7, 10, vec![1, 3, 7, 9]);
+    assert_eq!(ite.next_category(), Some((7, 3)));
+    assert_eq!(ite.next_category(), Some((8, 0)));
+    assert_eq!(ite.next_category(), Some((9, 0)));
+    assert_eq!(ite.next_category(), Some((10, 0)));
+    assert_eq!(ite.next_category(), Some((11, 1)));
+    assert_eq!(ite.next_category(), Some((12, 0)));
+    assert_eq!(ite.next_category(), Some((13, 2)));
+    assert_eq!(ite.next_category(), None);
+    assert_eq!(
+      ite.categories(),
+      vec!["", "1 mod 10", "3 mod 10", "7 mod 10", "9 mod 10"]
+    );
+  }
+}
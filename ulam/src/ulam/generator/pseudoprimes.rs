@@ -0,0 +1,242 @@
+use crate::ulam::generator::factors::FactorSieve;
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::primality::euler_probable_prime;
+use crate::ulam::generator::primality::fermat_probable_prime;
+use crate::ulam::generator::primality::strong_probable_prime;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Test {
+  /// Composite n with b^(n-1) = 1 mod n.
+  Fermat,
+  /// Odd composite n passing Miller–Rabin.
+  Strong,
+  /// Odd composite n with b^((n-1)/2) = (b/n) mod n.
+  EulerJacobi,
+  /// Composite n fooling Fermat's test to every coprime base, decided by
+  /// Korselt's criterion.
+  Carmichael,
+}
+
+impl Test {
+  pub fn from_name(name: &str) -> Option<Test> {
+    match name {
+      "pseudoprimes" => Some(Test::Fermat),
+      "strong-pseudoprimes" => Some(Test::Strong),
+      "euler-pseudoprimes" => Some(Test::EulerJacobi),
+      "carmichael" => Some(Test::Carmichael),
+      _ => None,
+    }
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      Test::Fermat => "pseudoprimes",
+      Test::Strong => "strong-pseudoprimes",
+      Test::EulerJacobi => "euler-pseudoprimes",
+      Test::Carmichael => "carmichael",
+    }
+  }
+}
+
+/// Marks composite numbers that pass a probable prime test to every one of
+/// the bases. With `with_primes`, primes are drawn too, as a category of
+/// their own, so the pseudoprimes stand out among them.
+pub struct PseudoprimeGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  test: Test,
+  bases: Vec<usize>,
+  primes: bool,
+  sieve: FactorSieve,
+}
+
+impl PseudoprimeGenerator {
+  pub fn new(n: usize, skip: usize, test: Test, bases: Vec<usize>) -> Self {
+    PseudoprimeGenerator {
+      i: skip,
+      max: n,
+      skip,
+      test,
+      bases,
+      primes: false,
+      sieve: FactorSieve::new(n),
+    }
+  }
+
+  pub fn with_primes(mut self) -> Self {
+    self.primes = true;
+    self
+  }
+
+  /// `bases` are separated by `,`, such as `2,3`. Carmichael numbers ignore
+  /// them.
+  pub fn from_gp(gp: &str, test: Test, bases: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+    FactorSieve::check(to)?;
+
+    let bases = bases
+      .split(",")
+      .map(|b| b.trim().parse())
+      .collect::<Result<Vec<usize>, _>>()?;
+    if bases.is_empty() || bases.iter().any(|&b| b < 2) {
+      return Err("bases must be at least 2".into());
+    }
+
+    Ok(Self::new(to, from, test, bases))
+  }
+
+  // Whether the composite n passes the test.
+  fn passes(&mut self, n: usize) -> bool {
+    match self.test {
+      Test::Fermat => self.bases.iter().all(|b| fermat_probable_prime(&n, b)),
+      Test::Strong => {
+        !n.is_multiple_of(2) && self.bases.iter().all(|b| strong_probable_prime(&n, b))
+      }
+      Test::EulerJacobi => {
+        !n.is_multiple_of(2) && self.bases.iter().all(|b| euler_probable_prime(&n, b))
+      }
+      Test::Carmichael => {
+        let factors = self.sieve.factorize(n);
+        factors.len() >= 2
+          && factors
+            .iter()
+            .all(|&(p, e)| e == 1 && (n - 1).is_multiple_of(p - 1))
+      }
+    }
+  }
+}
+
+impl Generator for PseudoprimeGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    if self.primes {
+      vec!["", "prime", "pseudoprime"]
+        .into_iter()
+        .map(|c| c.to_string())
+        .collect()
+    } else {
+      vec!["false".to_string(), "true".to_string()]
+    }
+  }
+
+  fn next_category(&mut self) -> Option<(usize, usize)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    if i < 4 {
+      let prime = i >= 2 && self.primes;
+      return Some((i, prime as usize));
+    }
+
+    let factors = self.sieve.factorize(i);
+    if factors == [(i, 1)] {
+      return Some((i, self.primes as usize));
+    }
+
+    if self.passes(i) {
+      return Some((i, 1 + self.primes as usize));
+    }
+
+    Some((i, 0))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    let bases = match self.test {
+      Test::Carmichael => String::new(),
+      _ => {
+        let bases: Vec<String> = self.bases.iter().map(|b| b.to_string()).collect();
+        format!(", bases = {}", bases.join(","))
+      }
+    };
+    format!(
+      "generator = {}, from = {}, to = {}{}",
+      self.test.name(),
+      self.skip,
+      self.max,
+      bases
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn marked(test: Test, n: usize, bases: Vec<usize>) -> Vec<usize> {
+    let mut gen = PseudoprimeGenerator::new(n, 0, test, bases);
+    std::iter::from_fn(|| gen.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect()
+  }
+
+  #[test]
+  fn test_tests() {
+    assert_eq!(
+      marked(Test::Fermat, 2000, vec![2]),
+      vec![341, 561, 645, 1105, 1387, 1729, 1905]
+    );
+    assert_eq!(marked(Test::Fermat, 2000, vec![2, 3]), vec![1105, 1729]);
+    assert_eq!(
+      marked(Test::Strong, 10000, vec![2]),
+      vec![2047, 3277, 4033, 4681, 8321]
+    );
+    assert_eq!(
+      marked(Test::EulerJacobi, 2000, vec![2]),
+      vec![561, 1105, 1729, 1905]
+    );
+    assert_eq!(
+      marked(Test::Carmichael, 10000, vec![]),
+      vec![561, 1105, 1729, 2465, 2821, 6601, 8911]
+    );
+  }
+
+  #[test]
+  fn test_with_primes() {
+    let mut gen = PseudoprimeGenerator::from_gp("339:347", Test::Fermat, "2")
+      .unwrap()
+      .with_primes();
+    let categories: Vec<_> = std::iter::from_fn(|| gen.next_category()).collect();
+    assert_eq!(
+      categories,
+      vec![
+        (339, 0),
+        (340, 0),
+        (341, 2),
+        (342, 0),
+        (343, 0),
+        (344, 0),
+        (345, 0),
+        (346, 0),
+        (347, 1)
+      ]
+    );
+    assert_eq!(gen.categories(), vec!["", "prime", "pseudoprime"]);
+    assert_eq!(
+      gen.generator_info(),
+      "generator = pseudoprimes, from = 339, to = 347, bases = 2"
+    );
+    assert!(PseudoprimeGenerator::from_gp("1:100", Test::Strong, "1").is_err());
+  }
+}
@@ -0,0 +1,320 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Seed for a run without `--seed`, taken from the clock.
+pub fn random_seed() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_nanos() as u64)
+    .unwrap_or(0)
+}
+
+// The k-th output of SplitMix64 from `seed`, in [0, 1). The draw for n does
+// not depend on the draws before it, so a window shows the same marks
+// wherever it starts.
+fn uniform(seed: u64, k: u64) -> f64 {
+  let mut z = seed.wrapping_add(k.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA));
+  z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+  z ^= z >> 31;
+  (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn parse_range(gp: &str) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+  let mut gp = gp.split(":");
+  let from = gp.next();
+  let to = gp.next();
+
+  let from: usize = if let Some(from) = from {
+    from.parse()?
+  } else {
+    1
+  };
+
+  let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+  Ok((from, to))
+}
+
+/// Cramér's model of the primes: n is marked with probability 1 / ln n.
+pub struct CramerGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  seed: u64,
+}
+
+impl CramerGenerator {
+  pub fn new(n: usize, skip: usize, seed: u64) -> Self {
+    CramerGenerator {
+      i: skip,
+      max: n,
+      skip,
+      seed,
+    }
+  }
+
+  pub fn from_gp(gp: &str, seed: u64) -> Result<Self, Box<dyn std::error::Error>> {
+    let (from, to) = parse_range(gp)?;
+    Ok(Self::new(to, from, seed))
+  }
+}
+
+impl Generator for CramerGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    // 1 / ln 2 exceeds 1, so 2 is always marked.
+    Some((
+      i,
+      i >= 2 && uniform(self.seed, i as u64) * (i as f64).ln() < 1.0,
+    ))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = cramer, from = {}, to = {}, seed = {}",
+      self.skip, self.max, self.seed
+    )
+  }
+}
+
+/// Marks each n with the same probability.
+pub struct BernoulliGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  density: f64,
+  seed: u64,
+}
+
+impl BernoulliGenerator {
+  pub fn new(n: usize, skip: usize, density: f64, seed: u64) -> Self {
+    BernoulliGenerator {
+      i: skip,
+      max: n,
+      skip,
+      density,
+      seed,
+    }
+  }
+
+  /// `gp` is `from:to:density`, with the density 0.1 by default.
+  pub fn from_gp(gp: &str, seed: u64) -> Result<Self, Box<dyn std::error::Error>> {
+    let (from, to) = parse_range(gp)?;
+    let density: f64 = if let Some(density) = gp.split(":").nth(2) {
+      density.parse()?
+    } else {
+      0.1
+    };
+
+    if !(0.0..=1.0).contains(&density) {
+      return Err(format!("density must be between 0 and 1({})", density).into());
+    }
+
+    Ok(Self::new(to, from, density, seed))
+  }
+}
+
+impl Generator for BernoulliGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    Some((i, uniform(self.seed, i as u64) < self.density))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = bernoulli, from = {}, to = {}, density = {}, seed = {}",
+      self.skip, self.max, self.density, self.seed
+    )
+  }
+}
+
+/// Shuffles the categories of a source over its window, keeping how many
+/// numbers each category has. A source drawn as a heat map has its values
+/// shuffled instead. The whole window of the source is read up front.
+pub struct ShuffledGenerator<N = usize> {
+  info: String,
+  categories: Vec<String>,
+  range: Option<(f64, f64)>,
+  numbers: Vec<(N, usize, Option<f64>)>,
+  pos: usize,
+}
+
+impl<N: Number> ShuffledGenerator<N> {
+  pub fn new(mut source: Box<dyn Generator<N>>, seed: u64) -> Self {
+    let range = source.value_range();
+    let mut numbers: Vec<(N, usize, Option<f64>)> = if range.is_some() {
+      std::iter::from_fn(|| source.next_value())
+        .map(|(n, v)| (n, v.is_some() as usize, v))
+        .collect()
+    } else {
+      std::iter::from_fn(|| source.next_category())
+        .map(|(n, c)| (n, c, (c != 0).then_some(c as f64)))
+        .collect()
+    };
+
+    // Fisher–Yates over the categories and values, with the numbers left in
+    // place.
+    for i in (1..numbers.len()).rev() {
+      let j = (uniform(seed, i as u64) * (i + 1) as f64) as usize;
+      let (c, v) = (numbers[i].1, numbers[i].2);
+      numbers[i].1 = numbers[j].1;
+      numbers[i].2 = numbers[j].2;
+      numbers[j].1 = c;
+      numbers[j].2 = v;
+    }
+
+    ShuffledGenerator {
+      info: format!("{}, shuffled, seed = {}", source.generator_info(), seed),
+      categories: source.categories(),
+      range,
+      numbers,
+      pos: 0,
+    }
+  }
+
+  fn next_number(&mut self) -> Option<(N, usize, Option<f64>)> {
+    let next = self.numbers.get(self.pos).cloned();
+    self.pos += 1;
+    next
+  }
+}
+
+impl<N: Number> Generator<N> for ShuffledGenerator<N> {
+  fn data_num(&self) -> usize {
+    self.numbers.len().saturating_sub(1)
+  }
+
+  fn next(&mut self) -> std::option::Option<(N, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    self.categories.clone()
+  }
+
+  fn next_category(&mut self) -> Option<(N, usize)> {
+    self.next_number().map(|(n, c, _)| (n, c))
+  }
+
+  fn value_range(&self) -> Option<(f64, f64)> {
+    self.range
+  }
+
+  fn next_value(&mut self) -> Option<(N, Option<f64>)> {
+    self.next_number().map(|(n, _, v)| (n, v))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.info.clone()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ulam::generator::divisors::DivisorsGenerator;
+  use crate::ulam::generator::primes::PrimesGenerator;
+
+  fn marked(gen: &mut dyn Generator) -> Vec<usize> {
+    std::iter::from_fn(|| gen.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect()
+  }
+
+  #[test]
+  fn test_cramer() {
+    let all = marked(&mut CramerGenerator::new(100_000, 0, 42));
+    // π(10^5) = 9592; the model expects li(10^5) ≈ 9630 marks.
+    assert!((9000..10300).contains(&all.len()), "{}", all.len());
+    assert_eq!(all[0], 2);
+
+    // The marks of a window do not depend on where it starts.
+    let window = marked(&mut CramerGenerator::new(2000, 1000, 42));
+    let expected: Vec<_> = all
+      .iter()
+      .copied()
+      .filter(|&n| (1000..=2000).contains(&n))
+      .collect();
+    assert_eq!(window, expected);
+    assert_ne!(marked(&mut CramerGenerator::new(2000, 1000, 43)), expected);
+
+    assert_eq!(
+      CramerGenerator::from_gp("1:100", 7)
+        .unwrap()
+        .generator_info(),
+      "generator = cramer, from = 1, to = 100, seed = 7"
+    );
+  }
+
+  #[test]
+  fn test_bernoulli() {
+    let count = marked(&mut BernoulliGenerator::from_gp("1:100000:0.25", 1).unwrap()).len();
+    assert!((24000..26000).contains(&count), "{}", count);
+    assert!(BernoulliGenerator::from_gp("1:100:1.5", 1).is_err());
+  }
+
+  #[test]
+  fn test_shuffled() {
+    let mut gen = ShuffledGenerator::new(Box::new(PrimesGenerator::new(1000, 1)), 3);
+    assert_eq!(gen.data_num(), 999);
+    let numbers: Vec<_> = std::iter::from_fn(|| gen.next()).collect();
+    assert_eq!(numbers.len(), 1000);
+    assert!(numbers.iter().map(|&(n, _)| n).eq(1..=1000));
+    assert_eq!(numbers.iter().filter(|&&(_, b)| b).count(), 168);
+    assert_ne!(
+      marked(&mut ShuffledGenerator::new(
+        Box::new(PrimesGenerator::new(1000, 1)),
+        3
+      )),
+      marked(&mut PrimesGenerator::new(1000, 1))
+    );
+    assert_eq!(
+      gen.generator_info(),
+      "generator = primes, from = 1, to = 1000, shuffled, seed = 3"
+    );
+  }
+
+  #[test]
+  fn test_shuffled_values() {
+    let mut source = DivisorsGenerator::new(1000, 1);
+    let range = source.value_range();
+    let mut expected: Vec<_> = std::iter::from_fn(|| source.next_value())
+      .map(|(_, v)| v.unwrap())
+      .collect();
+
+    let mut gen = ShuffledGenerator::new(Box::new(DivisorsGenerator::new(1000, 1)), 3);
+    assert_eq!(gen.value_range(), range);
+    let numbers: Vec<_> = std::iter::from_fn(|| gen.next_value()).collect();
+    assert!(numbers.iter().map(|&(n, _)| n).eq(1..=1000));
+
+    let mut values: Vec<_> = numbers.iter().map(|&(_, v)| v.unwrap()).collect();
+    assert_ne!(values, expected);
+    values.sort_by(f64::total_cmp);
+    expected.sort_by(f64::total_cmp);
+    assert_eq!(values, expected);
+  }
+}
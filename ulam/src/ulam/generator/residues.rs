@@ -0,0 +1,137 @@
+use crate::ulam::generator::generator::Generator;
+
+/// Categorizes n by its residue modulo k. Residue r is category r + 1, so
+/// every number is drawn. With a single residue r it only marks n ≡ r (mod k).
+pub struct ResiduesGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  modulus: usize,
+  residue: Option<usize>,
+}
+
+impl ResiduesGenerator {
+  pub fn new(n: usize, modulus: usize, skip: usize) -> Self {
+    ResiduesGenerator {
+      i: skip,
+      max: n,
+      skip,
+      modulus,
+      residue: None,
+    }
+  }
+
+  /// Marks only n ≡ `residue` (mod k).
+  pub fn with_residue(mut self, residue: usize) -> Self {
+    self.residue = Some(residue % self.modulus);
+    self
+  }
+
+  pub fn from_gp(gp: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+    let modulus = gp.next();
+    let residue = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    let modulus: usize = if let Some(modulus) = modulus {
+      modulus.parse()?
+    } else {
+      4
+    };
+
+    if modulus == 0 {
+      return Err("modulus must be positive".into());
+    }
+
+    let gen = Self::new(to, modulus, from);
+    if let Some(residue) = residue {
+      return Ok(gen.with_residue(residue.parse()?));
+    }
+
+    Ok(gen)
+  }
+}
+
+impl Generator for ResiduesGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_category().map(|(n, c)| (n, c != 0))
+  }
+
+  fn categories(&self) -> Vec<String> {
+    if let Some(residue) = self.residue {
+      return vec![String::new(), format!("{} mod {}", residue, self.modulus)];
+    }
+
+    let mut categories = vec![String::new()];
+    categories.extend((0..self.modulus).map(|r| format!("{} mod {}", r, self.modulus)));
+    categories
+  }
+
+  fn next_category(&mut self) -> Option<(usize, usize)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    let r = i % self.modulus;
+    match self.residue {
+      Some(residue) => Some((i, (r == residue) as usize)),
+      None => Some((i, r + 1)),
+    }
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    if let Some(residue) = self.residue {
+      return format!(
+        "generator = residues, from = {}, to = {}, mod = {}, residue = {}",
+        self.skip, self.max, self.modulus, residue
+      );
+    }
+
+    format!(
+      "generator = residues, from = {}, to = {}, mod = {}",
+      self.skip, self.max, self.modulus
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let mut ite = ResiduesGenerator::new(7, 3, 4);
+    assert_eq!(ite.next_category(), Some((4, 2)));
+    assert_eq!(ite.next_category(), Some((5, 3)));
+    assert_eq!(ite.next_category(), Some((6, 1)));
+    assert_eq!(ite.next_category(), Some((7, 2)));
+    assert_eq!(ite.next_category(), None);
+    assert_eq!(ite.categories(), vec!["", "0 mod 3", "1 mod 3", "2 mod 3"]);
+  }
+
+  #[test]
+  fn test_residue() {
+    let mut ite = ResiduesGenerator::from_gp("4:7:3:5").unwrap();
+    assert_eq!(ite.next(), Some((4, false)));
+    assert_eq!(ite.next(), Some((5, true)));
+    assert_eq!(ite.next(), Some((6, false)));
+    assert_eq!(ite.next(), Some((7, false)));
+    assert_eq!(ite.next(), None);
+    assert_eq!(ite.categories(), vec!["", "2 mod 3"]);
+  }
+}
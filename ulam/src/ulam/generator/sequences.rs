@@ -0,0 +1,387 @@
+use crate::ulam::generator::generator::Generator;
+
+// Ulam numbers are found with bitsets over every number up to the end of the
+// window, and each new term updates the words up to twice it, so the work
+// grows with the square of the window end.
+const ULAM_LIMIT: usize = 1 << 21;
+
+// Lucky numbers are sieved in a Fenwick tree over every odd number up to the
+// end of the window.
+const LUCKY_LIMIT: usize = 1 << 24;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Sequence {
+  /// Ulam numbers from (u, v): each further term is the smallest number
+  /// that is the sum of two distinct earlier terms in exactly one way.
+  Ulam(usize, usize),
+  /// Numbers left by the sieve of Josephus Flavius: from the odd numbers,
+  /// for each remaining l in turn, every l-th remaining number is removed.
+  Lucky,
+  /// 0, 1, 1, 2, 3, 5, ...
+  Fibonacci,
+  /// 2, 1, 3, 4, 7, 11, ...
+  Lucas,
+  /// 1, 1, 1, 2, 2, 3, 4, 5, ..., P(k) = P(k - 2) + P(k - 3).
+  Padovan,
+}
+
+impl Sequence {
+  pub fn from_name(name: &str) -> Option<Sequence> {
+    match name {
+      "ulam" => Some(Sequence::Ulam(1, 2)),
+      "lucky" => Some(Sequence::Lucky),
+      "fibonacci" => Some(Sequence::Fibonacci),
+      "lucas" => Some(Sequence::Lucas),
+      "padovan" => Some(Sequence::Padovan),
+      _ => None,
+    }
+  }
+
+  fn name(&self) -> String {
+    match *self {
+      Sequence::Ulam(u, v) => format!("ulam, u = {}, v = {}", u, v),
+      Sequence::Lucky => "lucky".to_string(),
+      Sequence::Fibonacci => "fibonacci".to_string(),
+      Sequence::Lucas => "lucas".to_string(),
+      Sequence::Padovan => "padovan".to_string(),
+    }
+  }
+
+  /// Largest window end the terms are computed up to, for the sequences
+  /// whose terms are sieved from every number below it.
+  pub fn limit(&self) -> Option<usize> {
+    match *self {
+      Sequence::Ulam(_, _) => Some(ULAM_LIMIT),
+      Sequence::Lucky => Some(LUCKY_LIMIT),
+      _ => None,
+    }
+  }
+
+  /// Terms up to `max` in increasing order, without repeats.
+  pub fn terms(&self, max: usize) -> Vec<usize> {
+    let mut terms = match *self {
+      Sequence::Ulam(u, v) => ulam(u, v, max),
+      Sequence::Lucky => lucky(max),
+      Sequence::Fibonacci => recurrence(&[0, 1], &[1, 1], max),
+      Sequence::Lucas => recurrence(&[2, 1], &[1, 1], max),
+      Sequence::Padovan => recurrence(&[1, 1, 1], &[1, 1, 0], max),
+    };
+    terms.sort_unstable();
+    terms.dedup();
+    terms
+  }
+}
+
+// Terms of a linear recurrence a(k) = Σ coefficients[j] a(k - len + j) up to
+// max.
+fn recurrence(initial: &[usize], coefficients: &[usize], max: usize) -> Vec<usize> {
+  let mut terms: Vec<usize> = initial.iter().copied().filter(|&t| t <= max).collect();
+  let mut window = initial.to_vec();
+  loop {
+    let next = window
+      .iter()
+      .zip(coefficients)
+      .try_fold(0usize, |sum, (&t, &c)| sum.checked_add(t.checked_mul(c)?));
+    match next {
+      Some(next) if next <= max => {
+        terms.push(next);
+        window.remove(0);
+        window.push(next);
+      }
+      _ => return terms,
+    }
+  }
+}
+
+// Sums of two distinct terms are counted up to 2 in the bit sets `ones` and
+// `twos`. A new term a adds the terms so far shifted by a, 64 sums at a time,
+// so when the candidate m is reached its count is complete.
+fn ulam(u: usize, v: usize, max: usize) -> Vec<usize> {
+  let mut terms: Vec<usize> = [u, v].iter().copied().filter(|&t| t <= max).collect();
+  if v >= max {
+    return terms;
+  }
+
+  let words = max / 64 + 2;
+  let mut sums = UlamSums {
+    max,
+    terms: vec![0; words],
+    ones: vec![0; words],
+    twos: vec![0; words],
+  };
+  sums.add(u);
+  sums.add(v);
+  for m in v + 1..=max {
+    if get_bit(&sums.ones, m) && !get_bit(&sums.twos, m) {
+      terms.push(m);
+      sums.add(m);
+    }
+  }
+
+  terms
+}
+
+struct UlamSums {
+  max: usize,
+  terms: Vec<u64>,
+  ones: Vec<u64>,
+  twos: Vec<u64>,
+}
+
+impl UlamSums {
+  fn add(&mut self, a: usize) {
+    // Word w of the sums is a plus bits 64w - a to 64w - a + 63 of the terms,
+    // all of which are below a.
+    for w in a / 64..=(2 * a).min(self.max) / 64 {
+      let pos = 64 * w as isize - a as isize;
+      let x = if pos >= 0 {
+        bits_at(&self.terms, pos as usize)
+      } else {
+        self.terms[0] << -pos
+      };
+      self.twos[w] |= self.ones[w] & x;
+      self.ones[w] |= x;
+    }
+
+    self.terms[a / 64] |= 1 << (a % 64);
+  }
+}
+
+fn get_bit(words: &[u64], pos: usize) -> bool {
+  words[pos / 64] >> (pos % 64) & 1 == 1
+}
+
+// The 64 bits from bit `pos` on.
+fn bits_at(words: &[u64], pos: usize) -> u64 {
+  let (i, shift) = (pos / 64, pos % 64);
+  if shift == 0 {
+    words[i]
+  } else {
+    words[i] >> shift | words[i + 1] << (64 - shift)
+  }
+}
+
+// Fenwick tree over the odd numbers, each present or removed, so the k-th
+// remaining one is found in O(log n).
+struct Fenwick {
+  tree: Vec<usize>,
+}
+
+impl Fenwick {
+  fn filled(n: usize) -> Self {
+    let mut tree = vec![0; n + 1];
+    for i in 1..=n {
+      tree[i] += 1;
+      let parent = i + (i & i.wrapping_neg());
+      if parent <= n {
+        tree[parent] += tree[i];
+      }
+    }
+
+    Fenwick { tree }
+  }
+
+  fn remove(&mut self, mut i: usize) {
+    while i < self.tree.len() {
+      self.tree[i] -= 1;
+      i += i & i.wrapping_neg();
+    }
+  }
+
+  // Index of the k-th remaining entry, counted from 1.
+  fn kth(&self, mut k: usize) -> usize {
+    let mut i = 0;
+    let mut step = (self.tree.len() - 1).next_power_of_two();
+    while step > 0 {
+      if i + step < self.tree.len() && self.tree[i + step] < k {
+        i += step;
+        k -= self.tree[i];
+      }
+      step /= 2;
+    }
+
+    i + 1
+  }
+}
+
+fn lucky(max: usize) -> Vec<usize> {
+  // Entry i stands for the odd number 2i - 1.
+  let n = max.div_ceil(2);
+  let mut fenwick = Fenwick::filled(n);
+  let mut count = n;
+  let mut position = 2;
+  while position <= count {
+    let l = 2 * fenwick.kth(position) - 1;
+    if l > count {
+      break;
+    }
+
+    // From the back, so earlier positions keep their rank.
+    let mut j = count / l * l;
+    while j >= l {
+      fenwick.remove(fenwick.kth(j));
+      j -= l;
+    }
+    count -= count / l;
+    position += 1;
+  }
+
+  (1..=count).map(|k| 2 * fenwick.kth(k) - 1).collect()
+}
+
+/// Marks the terms of an integer sequence. The terms up to the end of the
+/// window are computed up front.
+pub struct SequenceGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  sequence: Sequence,
+  terms: Vec<usize>,
+  pos: usize,
+}
+
+impl SequenceGenerator {
+  pub fn new(n: usize, skip: usize, sequence: Sequence) -> Self {
+    let terms = sequence.terms(n);
+    SequenceGenerator {
+      i: skip,
+      max: n,
+      skip,
+      sequence,
+      pos: terms.partition_point(|&t| t < skip),
+      terms,
+    }
+  }
+
+  /// `gp` is `from:to:u:v`, where the Ulam sequence starts from u and v,
+  /// 1 and 2 by default.
+  pub fn from_gp(gp: &str, sequence: Sequence) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+    let u = gp.next();
+    let v = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+
+    let sequence = match sequence {
+      Sequence::Ulam(default_u, default_v) => {
+        let u: usize = if let Some(u) = u {
+          u.parse()?
+        } else {
+          default_u
+        };
+        let v: usize = if let Some(v) = v {
+          v.parse()?
+        } else {
+          default_v
+        };
+        if u == 0 || u >= v {
+          return Err("ulam needs 0 < u < v".into());
+        }
+
+        Sequence::Ulam(u, v)
+      }
+      sequence => sequence,
+    };
+
+    if let Some(limit) = sequence.limit().filter(|&limit| to > limit) {
+      return Err(format!("{} goes up to {}({})", sequence.name(), limit, to).into());
+    }
+
+    Ok(Self::new(to, from, sequence))
+  }
+}
+
+impl Generator for SequenceGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    if self.terms.get(self.pos) == Some(&i) {
+      self.pos += 1;
+      return Some((i, true));
+    }
+
+    Some((i, false))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = {}, from = {}, to = {}",
+      self.sequence.name(),
+      self.skip,
+      self.max
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_terms() {
+    assert_eq!(
+      Sequence::Ulam(1, 2).terms(100),
+      vec![
+        1, 2, 3, 4, 6, 8, 11, 13, 16, 18, 26, 28, 36, 38, 47, 48, 53, 57, 62, 69, 72, 77, 82, 87,
+        97, 99
+      ]
+    );
+    assert_eq!(
+      Sequence::Ulam(2, 5).terms(40),
+      vec![2, 5, 7, 9, 11, 12, 13, 15, 19, 23, 27, 29, 35, 37]
+    );
+    assert_eq!(
+      Sequence::Lucky.terms(100),
+      vec![1, 3, 7, 9, 13, 15, 21, 25, 31, 33, 37, 43, 49, 51, 63, 67, 69, 73, 75, 79, 87, 93, 99]
+    );
+    assert_eq!(
+      Sequence::Fibonacci.terms(100),
+      vec![0, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89]
+    );
+    assert_eq!(
+      Sequence::Lucas.terms(100),
+      vec![1, 2, 3, 4, 7, 11, 18, 29, 47, 76]
+    );
+    assert_eq!(
+      Sequence::Padovan.terms(50),
+      vec![1, 2, 3, 4, 5, 7, 9, 12, 16, 21, 28, 37, 49]
+    );
+    assert_eq!(Sequence::Fibonacci.terms(usize::MAX).len(), 93);
+  }
+
+  #[test]
+  fn test_generate() {
+    let mut gen = SequenceGenerator::from_gp("5:9", Sequence::Fibonacci).unwrap();
+    let marked: Vec<_> = std::iter::from_fn(|| gen.next()).collect();
+    assert_eq!(
+      marked,
+      vec![(5, true), (6, false), (7, false), (8, true), (9, false)]
+    );
+    assert_eq!(
+      gen.generator_info(),
+      "generator = fibonacci, from = 5, to = 9"
+    );
+    assert!(SequenceGenerator::from_gp("1:100:3:2", Sequence::Ulam(1, 2)).is_err());
+    assert!(
+      SequenceGenerator::from_gp("1000000000000:1000000001000", Sequence::Ulam(1, 2)).is_err()
+    );
+    assert!(SequenceGenerator::from_gp("1:20000000", Sequence::Lucky).is_err());
+    assert!(SequenceGenerator::from_gp("1:100000000", Sequence::Fibonacci).is_ok());
+  }
+}
@@ -0,0 +1,170 @@
+// Bit of a residue modulo 30 in a segment byte, or 0 when the residue shares a
+// factor with 30. The 8 residues coprime to 30 fill one byte, so one byte
+// covers 30 consecutive numbers.
+const WHEEL_BIT: [u8; 30] = [
+  0, 1, 0, 0, 0, 0, 0, 2, 0, 0, 0, 4, 0, 8, 0, 0, 0, 16, 0, 32, 0, 0, 0, 64, 0, 0, 0, 0, 0, 128,
+];
+
+const SEGMENT_BYTES: usize = 1 << 15;
+
+/// Segmented sieve of Eratosthenes on a mod 30 wheel.
+///
+/// Only one segment of the number line is held at a time, so memory is bounded
+/// by the segment size plus the base primes up to `sqrt(max)`.
+pub struct SegmentedSieve {
+  max: usize,
+  base_primes: Vec<usize>,
+  segment_bytes: usize,
+  low: usize,
+  high: usize,
+  composites: Vec<u8>,
+}
+
+impl SegmentedSieve {
+  pub fn new(max: usize) -> Self {
+    Self::with_segment_bytes(max, SEGMENT_BYTES)
+  }
+
+  fn with_segment_bytes(max: usize, segment_bytes: usize) -> Self {
+    SegmentedSieve {
+      max,
+      base_primes: base_primes(max.isqrt()),
+      segment_bytes,
+      low: 0,
+      high: 0,
+      composites: vec![],
+    }
+  }
+
+  /// Tells whether `n` is prime. Consecutive queries are answered from the
+  /// current segment; a query outside it sieves the segment starting at `n`.
+  pub fn is_prime(&mut self, n: usize) -> bool {
+    debug_assert!(
+      n <= self.max,
+      "{} is beyond the sieve limit {}",
+      n,
+      self.max
+    );
+
+    let bit = WHEEL_BIT[n % 30];
+    if bit == 0 {
+      return n == 2 || n == 3 || n == 5;
+    }
+
+    if n == 1 {
+      return false;
+    }
+
+    if n < self.low || self.high <= n {
+      self.sieve_segment(n - n % 30);
+    }
+
+    self.composites[(n - self.low) / 30] & bit == 0
+  }
+
+  fn sieve_segment(&mut self, low: usize) {
+    let high = low.saturating_add(self.segment_bytes * 30);
+    self.low = low;
+    self.high = high;
+    self.composites.clear();
+    self.composites.resize(self.segment_bytes, 0);
+
+    for &p in &self.base_primes {
+      let square = p * p;
+      if square >= high {
+        break;
+      }
+
+      let mut m = if square >= low {
+        square
+      } else {
+        low.div_ceil(p) * p
+      };
+      if m % 2 == 0 {
+        m += p;
+      }
+
+      // Walk the odd multiples of p, keeping (m - low) / 30 and m % 30 in step
+      // so no division is needed inside the loop.
+      let step = 2 * p;
+      let (step_bytes, step_rest) = (step / 30, step % 30);
+      let mut index = (m - low) / 30;
+      let mut rest = m % 30;
+      while index < self.segment_bytes {
+        self.composites[index] |= WHEEL_BIT[rest];
+        index += step_bytes;
+        rest += step_rest;
+        if rest >= 30 {
+          rest -= 30;
+          index += 1;
+        }
+      }
+    }
+  }
+}
+
+// Primes from 7 up to `limit`; 2, 3 and 5 are taken care of by the wheel.
+fn base_primes(limit: usize) -> Vec<usize> {
+  primes_up_to(limit)
+    .into_iter()
+    .filter(|&p| WHEEL_BIT[p % 30] != 0)
+    .collect()
+}
+
+/// All primes up to `limit` by a plain sieve of Eratosthenes.
+pub fn primes_up_to(limit: usize) -> Vec<usize> {
+  let mut composite = vec![false; limit + 1];
+  let mut primes = vec![];
+  for i in 2..=limit {
+    if composite[i] {
+      continue;
+    }
+
+    primes.push(i);
+
+    let mut j = i * i;
+    while j <= limit {
+      composite[j] = true;
+      j += i;
+    }
+  }
+
+  primes
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn is_prime_naive(n: usize) -> bool {
+    n >= 2
+      && (2..)
+        .take_while(|d| d * d <= n)
+        .all(|d| !n.is_multiple_of(d))
+  }
+
+  #[test]
+  fn test_wheel() {
+    for (i, &r) in [1, 7, 11, 13, 17, 19, 23, 29].iter().enumerate() {
+      assert_eq!(WHEEL_BIT[r], 1 << i);
+    }
+    assert_eq!(WHEEL_BIT.iter().filter(|&&b| b != 0).count(), 8);
+  }
+
+  #[test]
+  fn test_is_prime() {
+    let max = 20000;
+    let mut sieve = SegmentedSieve::with_segment_bytes(max, 4);
+    for n in 0..=max {
+      assert_eq!(sieve.is_prime(n), is_prime_naive(n), "n = {}", n);
+    }
+  }
+
+  #[test]
+  fn test_is_prime_out_of_order() {
+    let mut sieve = SegmentedSieve::with_segment_bytes(1_000_000, 16);
+    for &n in &[999_983, 7, 524_287, 1, 999_981, 2, 65_537, 4_999, 5_001] {
+      assert_eq!(sieve.is_prime(n), is_prime_naive(n), "n = {}", n);
+    }
+  }
+}
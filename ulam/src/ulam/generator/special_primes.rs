@@ -0,0 +1,209 @@
+use crate::ulam::generator::digits::reverse;
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::primality::is_prime;
+use crate::ulam::generator::primes::PrimesGenerator;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Family {
+  /// p with 2p + 1 prime.
+  SophieGermain,
+  /// p with (p - 1) / 2 prime.
+  Safe,
+  /// p with p + 2 prime or a product of two primes.
+  Chen,
+  /// p that reads the same reversed in the base.
+  Palindromic,
+  /// p whose reversal in the base is a different prime.
+  Emirp,
+  /// p whose reversal in the base is prime, palindromes included.
+  Reversible,
+}
+
+impl Family {
+  pub fn from_name(name: &str) -> Option<Family> {
+    match name {
+      "sophie-germain" => Some(Family::SophieGermain),
+      "safe" => Some(Family::Safe),
+      "chen" => Some(Family::Chen),
+      "palindromic" => Some(Family::Palindromic),
+      "emirps" => Some(Family::Emirp),
+      "reversible" => Some(Family::Reversible),
+      _ => None,
+    }
+  }
+
+  // Whether membership depends on the base the digits are read in.
+  fn has_base(&self) -> bool {
+    matches!(
+      self,
+      Family::Palindromic | Family::Emirp | Family::Reversible
+    )
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      Family::SophieGermain => "sophie-germain",
+      Family::Safe => "safe",
+      Family::Chen => "chen",
+      Family::Palindromic => "palindromic",
+      Family::Emirp => "emirps",
+      Family::Reversible => "reversible",
+    }
+  }
+}
+
+/// Marks primes of a special family. Primes of the window come from
+/// `PrimesGenerator`; the numbers derived from them, which may lie beyond
+/// the window, are tested with `primality::is_prime`.
+pub struct SpecialPrimesGenerator {
+  gen: PrimesGenerator,
+  family: Family,
+  base: u128,
+}
+
+impl SpecialPrimesGenerator {
+  pub fn new(n: usize, skip: usize, family: Family, base: usize) -> Self {
+    Self::with_primes(PrimesGenerator::new(n, skip), family, base)
+  }
+
+  fn with_primes(gen: PrimesGenerator, family: Family, base: usize) -> Self {
+    SpecialPrimesGenerator {
+      gen,
+      family,
+      base: base as u128,
+    }
+  }
+
+  /// `gp` is `from:to:base`. The base, 10 by default, is only taken by the
+  /// families read in a base.
+  pub fn from_gp(gp: &str, family: Family) -> Result<Self, Box<dyn std::error::Error>> {
+    let base: usize = match gp.split(":").nth(2) {
+      Some(base) if family.has_base() => base.parse()?,
+      Some(base) => return Err(format!("{} takes no base({})", family.name(), base).into()),
+      None => 10,
+    };
+
+    if base < 2 {
+      return Err("base must be at least 2".into());
+    }
+
+    Ok(Self::with_primes(
+      PrimesGenerator::from_gp(gp)?,
+      family,
+      base,
+    ))
+  }
+
+  // Whether the prime p belongs to the family.
+  fn is_member(&self, p: usize) -> bool {
+    let p = p as u128;
+    match self.family {
+      Family::SophieGermain => is_prime(&(2 * p + 1)),
+      Family::Safe => p > 2 && is_prime(&((p - 1) / 2)),
+      Family::Chen => is_prime(&(p + 2)) || is_semiprime(p + 2),
+      Family::Palindromic => reverse(p, self.base) == p,
+      Family::Emirp => {
+        let r = reverse(p, self.base);
+        r != p && is_prime(&r)
+      }
+      Family::Reversible => is_prime(&reverse(p, self.base)),
+    }
+  }
+}
+
+// A composite m with no prime factor up to its cube root has exactly two.
+fn is_semiprime(m: u128) -> bool {
+  let mut d = 2;
+  while d * d * d <= m {
+    if m.is_multiple_of(d) {
+      return is_prime(&(m / d));
+    }
+    d += 1;
+  }
+
+  m > 1 && !is_prime(&m)
+}
+
+impl Generator for SpecialPrimesGenerator {
+  fn data_num(&self) -> usize {
+    self.gen.data_num()
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.gen.next().map(|(n, b)| (n, b && self.is_member(n)))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    let base = if self.family.has_base() && self.base != 10 {
+      format!(", base = {}", self.base)
+    } else {
+      String::new()
+    };
+    format!(
+      "generator = {}, from = {}, to = {}{}",
+      self.family.name(),
+      self.gen.skip(),
+      self.gen.max(),
+      base
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn marked(family: Family, n: usize, base: usize) -> Vec<usize> {
+    let mut gen = SpecialPrimesGenerator::new(n, 0, family, base);
+    std::iter::from_fn(|| gen.next())
+      .filter(|&(_, b)| b)
+      .map(|(n, _)| n)
+      .collect()
+  }
+
+  #[test]
+  fn test_families() {
+    assert_eq!(
+      marked(Family::SophieGermain, 100, 10),
+      vec![2, 3, 5, 11, 23, 29, 41, 53, 83, 89]
+    );
+    assert_eq!(
+      marked(Family::Safe, 200, 10),
+      vec![5, 7, 11, 23, 47, 59, 83, 107, 167, 179]
+    );
+    assert_eq!(
+      marked(Family::Chen, 50, 10),
+      vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 47]
+    );
+    assert_eq!(
+      marked(Family::Palindromic, 400, 10),
+      vec![2, 3, 5, 7, 11, 101, 131, 151, 181, 191, 313, 353, 373, 383]
+    );
+    assert_eq!(
+      marked(Family::Emirp, 200, 10),
+      vec![13, 17, 31, 37, 71, 73, 79, 97, 107, 113, 149, 157, 167, 179, 199]
+    );
+    // 11 = 1011b and 13 = 1101b reverse into each other.
+    assert_eq!(marked(Family::Reversible, 20, 2), vec![3, 5, 7, 11, 13, 17]);
+  }
+
+  #[test]
+  fn test_from_gp() {
+    let gen = SpecialPrimesGenerator::from_gp("1:20:2", Family::Reversible).unwrap();
+    assert_eq!(
+      gen.generator_info(),
+      "generator = reversible, from = 1, to = 20, base = 2"
+    );
+    assert!(SpecialPrimesGenerator::from_gp("1:20:1", Family::Emirp).is_err());
+    assert!(SpecialPrimesGenerator::from_gp("1:20:2", Family::Safe).is_err());
+  }
+
+  #[test]
+  fn test_is_semiprime() {
+    let semiprimes: Vec<u128> = (0..40).filter(|&m| is_semiprime(m)).collect();
+    assert_eq!(
+      semiprimes,
+      vec![4, 6, 9, 10, 14, 15, 21, 22, 25, 26, 33, 34, 35, 38, 39]
+    );
+  }
+}
@@ -1,19 +1,24 @@
 use crate::ulam::generator::generator::Generator;
-use core::iter::Skip;
-use std::collections::HashSet;
+use crate::ulam::generator::number::Number;
 
-pub struct SquareGenerator {
-  max: usize,
-  skip: usize,
-  iter: Skip<SquaresOrNotIterator>,
+pub struct SquareGenerator<N = usize> {
+  max: N,
+  skip: N,
+  iter: SquaresOrNotIterator<N>,
 }
 
 impl SquareGenerator {
   pub fn new(n: usize, skip: usize) -> SquareGenerator {
+    Self::with_bounds(n, skip)
+  }
+}
+
+impl<N: Number> SquareGenerator<N> {
+  pub fn with_bounds(n: N, skip: N) -> SquareGenerator<N> {
     SquareGenerator {
-      max: n,
-      skip,
-      iter: SquaresOrNotIterator::new(n).skip(skip),
+      max: n.clone(),
+      skip: skip.clone(),
+      iter: SquaresOrNotIterator::new(n, skip),
     }
   }
 
@@ -22,28 +27,30 @@ impl SquareGenerator {
     let from = gp.next();
     let to = gp.next();
 
-    let from: usize = if let Some(from) = from {
-      from.parse()?
+    let from: N = if let Some(from) = from {
+      N::parse(from)?
     } else {
-      1
+      N::from_usize(1)
     };
 
-    let to: usize = if let Some(to) = to {
-      to.parse()?
+    let to: N = if let Some(to) = to {
+      N::parse(to)?
     } else {
-      1000
+      N::from_usize(1000)
     };
 
-    Ok(Self::new(to, from))
+    Ok(Self::with_bounds(to, from))
   }
 }
 
-impl Generator for SquareGenerator {
+impl<N: Number> Generator<N> for SquareGenerator<N> {
   fn data_num(&self) -> usize {
-    self.max - self.skip
+    (self.max.clone() - self.skip.clone())
+      .to_usize()
+      .unwrap_or(usize::MAX)
   }
 
-  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+  fn next(&mut self) -> std::option::Option<(N, bool)> {
     self.iter.next()
   }
 
@@ -55,35 +62,43 @@ impl Generator for SquareGenerator {
   }
 }
 
-pub struct SquaresOrNotIterator {
-  i: usize,
-  max: usize,
-  squares: HashSet<usize>,
+pub struct SquaresOrNotIterator<N = usize> {
+  i: N,
+  max: N,
+  next_square: Option<N>,
 }
 
-impl SquaresOrNotIterator {
-  pub fn new(n: usize) -> SquaresOrNotIterator {
+impl<N: Number> SquaresOrNotIterator<N> {
+  pub fn new(n: N, from: N) -> SquaresOrNotIterator<N> {
+    let root = from.isqrt();
+    let root = if root.clone() * root.clone() == from {
+      root
+    } else {
+      root + N::from_usize(1)
+    };
+
     SquaresOrNotIterator {
-      i: 0,
+      i: from,
       max: n,
-      squares: HashSet::new(),
+      next_square: root.checked_mul(&root),
     }
   }
 }
 
-impl Iterator for SquaresOrNotIterator {
-  type Item = (usize, bool);
+impl<N: Number> Iterator for SquaresOrNotIterator<N> {
+  type Item = (N, bool);
 
   fn next(&mut self) -> Option<Self::Item> {
-    let i = self.i;
+    let i = self.i.clone();
     if self.i > self.max {
       return None;
     }
 
-    self.i += 1;
-    self.squares.insert(i * i);
+    self.i = i.clone() + N::from_usize(1);
 
-    if self.squares.contains(&i) {
+    if self.next_square.as_ref() == Some(&i) {
+      let root = i.isqrt() + N::from_usize(1);
+      self.next_square = root.checked_mul(&root);
       Some((i, true))
     } else {
       Some((i, false))
@@ -108,4 +123,23 @@ mod tests {
     assert_eq!(ite.next(), Some((10, false)));
     assert_eq!(ite.next(), None);
   }
+
+  #[test]
+  fn test_generate_far() {
+    let mut ite = SquareGenerator::new(1_000_000_000_001, 999_999_999_999);
+    assert_eq!(ite.next(), Some((999_999_999_999, false)));
+    assert_eq!(ite.next(), Some((1_000_000_000_000, true)));
+    assert_eq!(ite.next(), Some((1_000_000_000_001, false)));
+    assert_eq!(ite.next(), None);
+  }
+
+  #[test]
+  fn test_generate_u128() {
+    let square = (u64::MAX as u128 - 1) * (u64::MAX as u128 - 1);
+    let mut ite = SquareGenerator::with_bounds(square + 1, square - 1);
+    assert_eq!(ite.next(), Some((square - 1, false)));
+    assert_eq!(ite.next(), Some((square, true)));
+    assert_eq!(ite.next(), Some((square + 1, false)));
+    assert_eq!(ite.next(), None);
+  }
 }
@@ -1,20 +1,26 @@
 use crate::ulam::generator::generator::Generator;
-use core::iter::Skip;
+use crate::ulam::generator::number::Number;
 
-pub struct TimesGenerator {
-  max: usize,
-  skip: usize,
-  times_base: usize,
-  iter: Skip<TimesOrNotIterator>,
+pub struct TimesGenerator<N = usize> {
+  max: N,
+  skip: N,
+  times_base: N,
+  iter: TimesOrNotIterator<N>,
 }
 
 impl TimesGenerator {
   pub fn new(n: usize, times_base: usize, skip: usize) -> Self {
+    Self::with_bounds(n, times_base, skip)
+  }
+}
+
+impl<N: Number> TimesGenerator<N> {
+  pub fn with_bounds(n: N, times_base: N, skip: N) -> Self {
     TimesGenerator {
-      max: n,
-      skip,
-      times_base,
-      iter: TimesOrNotIterator::new(n, times_base).skip(skip),
+      max: n.clone(),
+      skip: skip.clone(),
+      times_base: times_base.clone(),
+      iter: TimesOrNotIterator::new(n, times_base, skip),
     }
   }
 
@@ -24,34 +30,36 @@ impl TimesGenerator {
     let to = gp.next();
     let times = gp.next();
 
-    let from: usize = if let Some(from) = from {
-      from.parse()?
+    let from: N = if let Some(from) = from {
+      N::parse(from)?
     } else {
-      1
+      N::from_usize(1)
     };
 
-    let to: usize = if let Some(to) = to {
-      to.parse()?
+    let to: N = if let Some(to) = to {
+      N::parse(to)?
     } else {
-      1000
+      N::from_usize(1000)
     };
 
-    let times: usize = if let Some(times) = times {
-      times.parse()?
+    let times: N = if let Some(times) = times {
+      N::parse(times)?
     } else {
-      2
+      N::from_usize(2)
     };
 
-    Ok(Self::new(to, times, from))
+    Ok(Self::with_bounds(to, times, from))
   }
 }
 
-impl Generator for TimesGenerator {
+impl<N: Number> Generator<N> for TimesGenerator<N> {
   fn data_num(&self) -> usize {
-    self.max - self.skip
+    (self.max.clone() - self.skip.clone())
+      .to_usize()
+      .unwrap_or(usize::MAX)
   }
 
-  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+  fn next(&mut self) -> std::option::Option<(N, bool)> {
     self.iter.next()
   }
 
@@ -63,34 +71,34 @@ impl Generator for TimesGenerator {
   }
 }
 
-pub struct TimesOrNotIterator {
-  i: usize,
-  max: usize,
-  times_base: usize,
+pub struct TimesOrNotIterator<N = usize> {
+  i: N,
+  max: N,
+  times_base: N,
 }
 
-impl TimesOrNotIterator {
-  pub fn new(n: usize, times_base: usize) -> TimesOrNotIterator {
+impl<N: Number> TimesOrNotIterator<N> {
+  pub fn new(n: N, times_base: N, from: N) -> TimesOrNotIterator<N> {
     TimesOrNotIterator {
-      i: 0,
+      i: from,
       max: n,
       times_base,
     }
   }
 }
 
-impl Iterator for TimesOrNotIterator {
-  type Item = (usize, bool);
+impl<N: Number> Iterator for TimesOrNotIterator<N> {
+  type Item = (N, bool);
 
   fn next(&mut self) -> Option<Self::Item> {
-    let i = self.i;
+    let i = self.i.clone();
     if self.i > self.max {
       return None;
     }
 
-    self.i += 1;
+    self.i = i.clone() + N::from_usize(1);
 
-    if i % self.times_base == 0 {
+    if i.clone() % self.times_base.clone() == N::from_usize(0) {
       Some((i, true))
     } else {
       Some((i, false))
@@ -113,4 +121,13 @@ mod tests {
     assert_eq!(ite.next(), Some((10, true)));
     assert_eq!(ite.next(), None);
   }
+
+  #[test]
+  fn test_generate_far() {
+    let mut ite = TimesGenerator::new(1_000_000_002, 3, 1_000_000_000);
+    assert_eq!(ite.next(), Some((1_000_000_000, false)));
+    assert_eq!(ite.next(), Some((1_000_000_001, false)));
+    assert_eq!(ite.next(), Some((1_000_000_002, true)));
+    assert_eq!(ite.next(), None);
+  }
 }
@@ -0,0 +1,98 @@
+use crate::ulam::generator::factors::FactorSieve;
+use crate::ulam::generator::generator::scan_value_range;
+use crate::ulam::generator::generator::Generator;
+
+/// Yields Euler's totient φ(n) as the value of n.
+pub struct TotientGenerator {
+  i: usize,
+  max: usize,
+  skip: usize,
+  sieve: FactorSieve,
+}
+
+impl TotientGenerator {
+  pub fn new(n: usize, skip: usize) -> Self {
+    TotientGenerator {
+      i: skip,
+      max: n,
+      skip,
+      sieve: FactorSieve::new(n),
+    }
+  }
+
+  pub fn from_gp(gp: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: usize = if let Some(from) = from {
+      from.parse()?
+    } else {
+      1
+    };
+
+    let to: usize = if let Some(to) = to { to.parse()? } else { 1000 };
+    FactorSieve::check(to)?;
+
+    Ok(Self::new(to, from))
+  }
+}
+
+impl Generator for TotientGenerator {
+  fn data_num(&self) -> usize {
+    self.max - self.skip
+  }
+
+  fn next(&mut self) -> std::option::Option<(usize, bool)> {
+    self.next_value().map(|(n, v)| (n, v.is_some()))
+  }
+
+  fn value_range(&self) -> Option<(f64, f64)> {
+    scan_value_range(&mut Self::new(self.max, self.skip))
+  }
+
+  fn next_value(&mut self) -> Option<(usize, Option<f64>)> {
+    let i = self.i;
+    if self.i > self.max {
+      return None;
+    }
+
+    self.i += 1;
+    if i == 0 {
+      return Some((i, None));
+    }
+
+    let phi = self
+      .sieve
+      .factorize(i)
+      .iter()
+      .fold(i, |phi, &(p, _)| phi / p * (p - 1));
+    Some((i, Some(phi as f64)))
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    format!(
+      "generator = totient, from = {}, to = {}",
+      self.skip, self.max
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generate() {
+    let mut ite = TotientGenerator::new(12, 0);
+    let values: Vec<_> = std::iter::from_fn(|| ite.next_value())
+      .map(|(_, v)| v)
+      .collect();
+    assert_eq!(values[0], None);
+    assert_eq!(
+      values[1..],
+      [1.0, 1.0, 2.0, 2.0, 4.0, 2.0, 6.0, 4.0, 6.0, 4.0, 10.0, 4.0].map(Some)
+    );
+    assert_eq!(ite.value_range(), Some((1.0, 10.0)));
+  }
+}
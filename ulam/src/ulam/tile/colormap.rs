@@ -0,0 +1,205 @@
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::RGBColor;
+use plotters::prelude::Rectangle;
+use plotters::prelude::ShapeStyle;
+use plotters::prelude::TextStyle;
+use plotters::prelude::BLACK;
+use plotters::style::IntoFont;
+use std::str::FromStr;
+
+// Evenly spaced samples of each map from low to high values.
+const VIRIDIS: [RGBColor; 10] = [
+  RGBColor(68, 1, 84),
+  RGBColor(72, 40, 120),
+  RGBColor(62, 73, 137),
+  RGBColor(49, 104, 142),
+  RGBColor(38, 130, 142),
+  RGBColor(31, 158, 137),
+  RGBColor(53, 183, 121),
+  RGBColor(110, 206, 88),
+  RGBColor(181, 222, 43),
+  RGBColor(253, 231, 37),
+];
+
+const MAGMA: [RGBColor; 10] = [
+  RGBColor(0, 0, 4),
+  RGBColor(24, 15, 61),
+  RGBColor(68, 15, 118),
+  RGBColor(114, 31, 129),
+  RGBColor(158, 47, 127),
+  RGBColor(205, 64, 113),
+  RGBColor(241, 96, 93),
+  RGBColor(253, 150, 104),
+  RGBColor(254, 202, 141),
+  RGBColor(252, 253, 191),
+];
+
+// Light to dark, so low values still stand out from the white background.
+const GRAYSCALE: [RGBColor; 2] = [RGBColor(230, 230, 230), RGBColor(0, 0, 0)];
+
+const DIVERGING: [RGBColor; 7] = [
+  RGBColor(33, 102, 172),
+  RGBColor(103, 169, 207),
+  RGBColor(209, 229, 240),
+  RGBColor(247, 247, 247),
+  RGBColor(253, 219, 199),
+  RGBColor(239, 138, 98),
+  RGBColor(178, 24, 43),
+];
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ColorMap {
+  Viridis,
+  Magma,
+  Grayscale,
+  Diverging,
+}
+
+impl ColorMap {
+  /// Colour at `t` in [0, 1].
+  pub fn color(&self, t: f64) -> RGBColor {
+    let samples: &[RGBColor] = match self {
+      ColorMap::Viridis => &VIRIDIS,
+      ColorMap::Magma => &MAGMA,
+      ColorMap::Grayscale => &GRAYSCALE,
+      ColorMap::Diverging => &DIVERGING,
+    };
+
+    let pos = t.clamp(0.0, 1.0) * (samples.len() - 1) as f64;
+    let i = (pos.floor() as usize).min(samples.len() - 2);
+    let f = pos - i as f64;
+    let (RGBColor(r0, g0, b0), RGBColor(r1, g1, b1)) = (samples[i], samples[i + 1]);
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * f).round() as u8;
+    RGBColor(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+  }
+}
+
+impl FromStr for ColorMap {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "viridis" => Ok(ColorMap::Viridis),
+      "magma" => Ok(ColorMap::Magma),
+      "grayscale" => Ok(ColorMap::Grayscale),
+      "diverging" => Ok(ColorMap::Diverging),
+      _ => Err(format!("Unknown colour map({})", s)),
+    }
+  }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Scale {
+  Linear,
+  Log,
+}
+
+impl FromStr for Scale {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "linear" => Ok(Scale::Linear),
+      "log" => Ok(Scale::Log),
+      _ => Err(format!("Unknown scale({})", s)),
+    }
+  }
+}
+
+/// Maps the values of a generator onto a colour map.
+pub struct HeatMap {
+  pub color_map: ColorMap,
+  pub scale: Scale,
+  range: (f64, f64),
+}
+
+impl HeatMap {
+  pub fn new(range: (f64, f64)) -> Self {
+    HeatMap {
+      color_map: ColorMap::Viridis,
+      scale: Scale::Linear,
+      range,
+    }
+  }
+
+  /// Position of `value` in the range, from 0 to 1. The log scale is taken
+  /// of the offset from the minimum plus one, so it copes with 0.
+  pub fn normalize(&self, value: f64) -> f64 {
+    let (min, max) = self.range;
+    if max <= min {
+      return 0.0;
+    }
+
+    match self.scale {
+      Scale::Linear => (value - min) / (max - min),
+      Scale::Log => (value - min).ln_1p() / (max - min).ln_1p(),
+    }
+  }
+
+  pub fn color(&self, value: f64) -> RGBColor {
+    self.color_map.color(self.normalize(value))
+  }
+
+  /// Draws a colour bar with the range at its ends, right aligned in `area`,
+  /// the strip below the tiles.
+  pub fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    let style = TextStyle::from(("sans-serif", 14).into_font()).color(&BLACK);
+    let (width, height) = area.dim_in_pixel();
+    let (bar_width, bar_height) = (200, 12);
+    let x = width as i32 - bar_width - 20;
+    let y = height as i32 - bar_height - 20;
+
+    for i in 0..bar_width {
+      let color = self.color_map.color(i as f64 / (bar_width - 1) as f64);
+      area.draw(&Rectangle::new(
+        [(x + i, y), (x + i + 1, y + bar_height)],
+        Into::<ShapeStyle>::into(&color).filled(),
+      ))?;
+    }
+
+    let (min, max) = self.range;
+    let max_label = max.to_string();
+    let max_width = area.estimate_text_size(&max_label, &style)?.0 as i32;
+    area.draw_text(&min.to_string(), &style, (x, y + bar_height + 2))?;
+    area.draw_text(
+      &max_label,
+      &style,
+      (x + bar_width - max_width, y + bar_height + 2),
+    )?;
+    if self.scale == Scale::Log {
+      let label_width = area.estimate_text_size("log", &style)?.0 as i32;
+      area.draw_text("log", &style, (x - label_width - 6, y - 1))?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_color() {
+    assert_eq!(ColorMap::Viridis.color(0.0), VIRIDIS[0]);
+    assert_eq!(ColorMap::Viridis.color(1.0), VIRIDIS[9]);
+    assert_eq!(ColorMap::Grayscale.color(0.5), RGBColor(115, 115, 115));
+    assert_eq!(ColorMap::Diverging.color(0.5), DIVERGING[3]);
+    assert_eq!(ColorMap::Magma.color(2.0), MAGMA[9]);
+  }
+
+  #[test]
+  fn test_normalize() {
+    let mut heat_map = HeatMap::new((1.0, 5.0));
+    assert_eq!(heat_map.normalize(1.0), 0.0);
+    assert_eq!(heat_map.normalize(3.0), 0.5);
+    heat_map.scale = Scale::Log;
+    assert_eq!(heat_map.normalize(5.0), 1.0);
+    assert!((heat_map.normalize(2.0) - 2f64.ln() / 5f64.ln()).abs() < 1e-12);
+  }
+}
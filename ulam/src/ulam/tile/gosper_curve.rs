@@ -0,0 +1,360 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::tile_options;
+use crate::ulam::tile::tile::Tile;
+use crate::ulam::tile::tile::MARGIN;
+use crate::ulam::tile::types::LabelMode;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::Rectangle;
+
+/// Lays numbers along the Gosper curve, the flowsnake, on the lattice of the
+/// hexagon spiral. The curve of order k runs through the 7^k cells of a
+/// Gosper island, each a neighbour of the last.
+pub struct GosperCurve<'a, 'b, N = usize> {
+  plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  tile: GosperCurveTile<'a, N>,
+  block: f64,
+  center: (f64, f64),
+  label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
+}
+
+impl<'a, 'b, N: Number> GosperCurve<'a, 'b, N> {
+  pub fn new(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> GosperCurve<'a, 'b, N> {
+    let n = gen.data_num();
+    let categories = gen.categories();
+    let heat_map = gen.value_range().map(HeatMap::new);
+    let mut order = 1;
+    while 7usize.checked_pow(order).is_some_and(|m| m < n + 1) {
+      order += 1;
+    }
+
+    // The island is not centred on the first cell.
+    let scales = scales(order);
+    let (min, max) = bounds(&scales, n + 1);
+
+    // A cell spans two in x.
+    let vw = ((max.0 - min.0) as f64 / 2.0).max((max.1 - min.1) as f64) + 1.0;
+    let range = plotting_area.get_pixel_range().0;
+    let block = (range.end - range.start - MARGIN as i32) as f64 / vw;
+    GosperCurve {
+      plotting_area,
+      tile: GosperCurve::tile(gen, scales),
+      block,
+      center: (
+        (min.0 + max.0 + 2) as f64 / 2.0,
+        (min.1 + max.1 + 1) as f64 / 2.0,
+      ),
+      label_mode: LabelMode::None,
+      categories,
+      heat_map,
+    }
+  }
+
+  pub fn from_tp(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::new(gen, plotting_area);
+
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      heat_map_option(tile.heat_map.as_mut(), key, value)?;
+    }
+
+    Ok(tile)
+  }
+
+  fn tile(gen: Box<dyn Generator<N> + 'a>, scales: Vec<(isize, isize)>) -> GosperCurveTile<'a, N> {
+    GosperCurveTile::new(gen, scales)
+  }
+
+  fn normalize(&self, x: isize, y: isize) -> (f64, f64) {
+    (
+      (x as f64 - self.center.0) * self.block / 2.0,
+      (y as f64 - self.center.1) * self.block,
+    )
+  }
+}
+
+impl<'a, 'b, N: Number> Tile<N> for GosperCurve<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+
+    let (n, x, y, color) = next?;
+    let coord1 = self.normalize(x, y);
+    let coord2 = self.normalize(x + 2, y + 1);
+    let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      (coord1.0, coord2.1),
+      font_size,
+      |style| {
+        self
+          .plotting_area
+          .draw(&Rectangle::new([coord1, coord2], style))
+      },
+    );
+
+    Some(r.map(|_| n))
+  }
+
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
+
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
+    }
+
+    Ok(())
+  }
+
+  fn tile_info(&self) -> std::string::String {
+    format!("tile = gosper, order = {}", self.tile.scales.len())
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
+  }
+}
+
+// Powers of ω = e^(iπ/3), with a + bω written (a, b).
+const OMEGA: [(isize, isize); 6] = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+
+// The curve of order 1 through a cell and its six neighbours. At order k
+// cell j, scaled up by (2 + ω)^(k - 1), is the centre of a block that is
+// the curve of order k - 1 turned by ω^r and run backwards where marked,
+// as A and B are in the L-system A → A-B--B+A++AA+B-.
+const CELLS: [(isize, isize); 7] = [(1, 0), (0, 1), (0, 0), (1, -1), (0, -1), (-1, 0), (-1, 1)];
+const BLOCKS: [(usize, bool); 7] = [
+  (0, false),
+  (4, true),
+  (0, true),
+  (2, false),
+  (0, false),
+  (0, false),
+  (2, true),
+];
+
+// Scale between orders, 2 + ω, of norm 7.
+const SCALE: (isize, isize) = (2, 1);
+
+// (a + bω)(c + dω) with ω² = ω - 1.
+fn mul((a, b): (isize, isize), (c, d): (isize, isize)) -> (isize, isize) {
+  (a * c - b * d, a * d + b * c + b * d)
+}
+
+// Scales of the cells of each level of the curve of the given order, the
+// powers of 2 + ω.
+fn scales(order: u32) -> Vec<(isize, isize)> {
+  let mut scales = vec![(1, 0)];
+  for level in 1..order as usize {
+    scales.push(mul(scales[level - 1], SCALE));
+  }
+
+  scales
+}
+
+// Offset and turn of the block of 7^level points holding the i-th, found
+// from the digits of i above `level`. The block is the curve of that order
+// turned and moved there, so the two also give the i-th point at level 0.
+fn block(scales: &[(isize, isize)], level: usize, mut i: usize) -> ((isize, isize), usize) {
+  let (mut a, mut b) = (0, 0);
+  let mut turn = 0;
+  for level in (level..scales.len()).rev() {
+    let size = 7usize.pow(level as u32);
+    let (j, rest) = (i / size, i % size);
+    let (r, backwards) = BLOCKS[j];
+    let (c, d) = mul(OMEGA[turn], mul(scales[level], CELLS[j]));
+    a += c;
+    b += d;
+
+    turn = (turn + r) % 6;
+    i = if backwards { size - 1 - rest } else { rest };
+  }
+
+  ((a, b), turn)
+}
+
+// Cell of the i-th point of the curve, in the doubled-x coordinates of the
+// hexagon spiral.
+fn gosper(scales: &[(isize, isize)], i: usize) -> (isize, isize) {
+  let ((a, b), _) = block(scales, 0, i);
+  (2 * a + b, b)
+}
+
+// The forms pa + qb giving x = 2a + b and y = b of a + bω, then each after
+// turning a + bω by ω^k, which takes pa + qb to qa + (q - p)b. Turning by ω³
+// negates.
+const FORMS: [[(isize, isize); 6]; 2] = [
+  [(2, 1), (1, -1), (-1, -2), (-2, -1), (-1, 1), (1, 2)],
+  [(0, 1), (1, 1), (1, 0), (0, -1), (-1, -1), (-1, 0)],
+];
+
+fn apply((p, q): (isize, isize), (a, b): (isize, isize)) -> isize {
+  p * a + q * b
+}
+
+// Lowest and highest x and y of the first n cells of the curve. Those cells
+// split into whole blocks, each the curve of a lower order turned and moved,
+// so the extremes follow from those of the curves of each order in every
+// turn without walking the cells.
+fn bounds(scales: &[(isize, isize)], n: usize) -> ((isize, isize), (isize, isize)) {
+  // Largest value of each form over the curve of each order.
+  let mut maxima = vec![[[0; 6]; 2]];
+  for level in 0..scales.len() {
+    let mut next = [[isize::MIN; 6]; 2];
+    for (j, &(r, _)) in BLOCKS.iter().enumerate() {
+      let offset = mul(scales[level], CELLS[j]);
+      for (f, forms) in FORMS.iter().enumerate() {
+        for (k, &form) in forms.iter().enumerate() {
+          let max = apply(form, offset) + maxima[level][f][(k + r) % 6];
+          next[f][k] = next[f][k].max(max);
+        }
+      }
+    }
+    maxima.push(next);
+  }
+
+  let (mut min, mut max) = ((isize::MAX, isize::MAX), (isize::MIN, isize::MIN));
+  let mut start = 0;
+  for level in (0..maxima.len()).rev() {
+    let size = match 7usize.checked_pow(level as u32) {
+      Some(size) => size,
+      None => continue,
+    };
+
+    while n - start >= size {
+      let (offset, turn) = block(scales, level, start);
+      let extreme =
+        |f: usize, k: usize| apply(FORMS[f][k], offset) + maxima[level][f][(k + turn) % 6];
+      min = (min.0.min(-extreme(0, 3)), min.1.min(-extreme(1, 3)));
+      max = (max.0.max(extreme(0, 0)), max.1.max(extreme(1, 0)));
+      start += size;
+    }
+  }
+
+  (min, max)
+}
+
+struct GosperCurveTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
+  scales: Vec<(isize, isize)>,
+  i: usize,
+}
+
+impl<'a, N: Number> GosperCurveTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>, scales: Vec<(isize, isize)>) -> Self {
+    GosperCurveTile { gen, scales, i: 0 }
+  }
+
+  fn next_with<T>(
+    &mut self,
+    next: impl FnOnce(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
+    if let Some((n, b)) = next(self.gen.as_mut()) {
+      let (x, y) = gosper(&self.scales, self.i);
+      self.i += 1;
+      Some((n, x, y, b))
+    } else {
+      None
+    }
+  }
+}
+
+impl<'a, N: Number> Iterator for GosperCurveTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::ulam::generator::primes::PrimesGenerator;
+  use std::collections::HashSet;
+
+  #[test]
+  fn test_curve() {
+    let points: Vec<_> = (0..7).map(|i| gosper(&scales(1), i)).collect();
+    assert_eq!(
+      points,
+      vec![(2, 0), (1, 1), (0, 0), (1, -1), (-1, -1), (-2, 0), (-1, 1)]
+    );
+
+    // Each cell is a neighbour of the last and none is visited twice.
+    let scales = scales(5);
+    let points: Vec<_> = (0..7usize.pow(5)).map(|i| gosper(&scales, i)).collect();
+    assert_eq!(points.iter().collect::<HashSet<_>>().len(), points.len());
+    for pair in points.windows(2) {
+      let (dx, dy) = (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+      assert!(
+        (dx.abs() == 2 && dy == 0) || (dx.abs() == 1 && dy.abs() == 1),
+        "{:?}",
+        pair
+      );
+    }
+  }
+
+  #[test]
+  fn test_bounds() {
+    let scales = scales(4);
+    let (mut min, mut max) = (gosper(&scales, 0), gosper(&scales, 0));
+    for n in 1..=7usize.pow(4) {
+      let (x, y) = gosper(&scales, n - 1);
+      min = (min.0.min(x), min.1.min(y));
+      max = (max.0.max(x), max.1.max(y));
+      assert_eq!(bounds(&scales, n), (min, max), "n = {}", n);
+    }
+  }
+
+  #[test]
+  fn test_tile() {
+    let gen = PrimesGenerator::new(3, 0);
+    let mut ite = GosperCurve::tile(Box::new(gen), scales(1));
+
+    assert_eq!(ite.next(), Some((0, 2, 0, false)));
+    assert_eq!(ite.next(), Some((1, 1, 1, false)));
+    assert_eq!(ite.next(), Some((2, 0, 0, true)));
+    assert_eq!(ite.next(), Some((3, 1, -1, true)));
+    assert_eq!(ite.next(), None);
+  }
+}
@@ -0,0 +1,365 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::tile_options;
+use crate::ulam::tile::tile::Tile;
+use crate::ulam::tile::tile::MARGIN;
+use crate::ulam::tile::types::LabelMode;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::Rectangle;
+
+/// Lays numbers in rows of a fixed width from the top left, each row left
+/// to right, or alternately left and right when boustrophedon. With the
+/// width set to a modulus, residue classes fall in columns.
+pub struct Grid<'a, 'b, N = usize> {
+  plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  tile: GridTile<'a, N>,
+  block: f64,
+  rows: usize,
+  label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
+}
+
+impl<'a, 'b, N: Number> Grid<'a, 'b, N> {
+  pub fn new(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> Grid<'a, 'b, N> {
+    let n = gen.data_num();
+    let categories = gen.categories();
+    let heat_map = gen.value_range().map(HeatMap::new);
+    let mut tile = Grid {
+      plotting_area,
+      tile: Grid::tile(gen, default_width(n)),
+      block: 0.0,
+      rows: 0,
+      label_mode: LabelMode::None,
+      categories,
+      heat_map,
+    };
+
+    tile.fit();
+    tile
+  }
+
+  pub fn from_tp(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::new(gen, plotting_area);
+
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      match key {
+        "W" => tile.tile.width = parse_width(value)?,
+        // Taken by `image_height`, which sizes the image before the tile.
+        "cell" => {
+          parse_cell(value)?;
+        }
+        "order" => {
+          tile.tile.boustrophedon = match value {
+            "row" => false,
+            "boustrophedon" => true,
+            _ => return Err(format!("Unknown order({})", value).into()),
+          }
+        }
+        _ => heat_map_option(tile.heat_map.as_mut(), key, value)?,
+      }
+    }
+
+    tile.fit();
+    Ok(tile)
+  }
+
+  fn tile(gen: Box<dyn Generator<N> + 'a>, width: usize) -> GridTile<'a, N> {
+    GridTile::new(gen, width)
+  }
+
+  // The rows follow from the width and the number of cells, and cells are
+  // as large as both sides of the plotting area allow. An image sized by
+  // `image_height` leaves the rows room for the cells the width gives.
+  fn fit(&mut self) {
+    let width = self.tile.width;
+    self.rows = rows(self.tile.gen.data_num(), width);
+    let (x, y) = self.plotting_area.get_pixel_range();
+    let across = (x.end - x.start - MARGIN as i32) as f64 / width as f64;
+    let down = (y.end - y.start - MARGIN as i32) as f64 / self.rows as f64;
+    self.block = across.min(down);
+  }
+
+  fn normalize(&self, x: isize, y: isize) -> (f64, f64) {
+    (
+      (x as f64 - self.tile.width as f64 / 2.0) * self.block,
+      (y as f64 - 1.0 + self.rows as f64 / 2.0) * self.block,
+    )
+  }
+}
+
+impl<'a, 'b, N: Number> Tile<N> for Grid<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+
+    let (n, x, y, color) = next?;
+    let coord1 = self.normalize(x, y);
+    let coord2 = self.normalize(x + 1, y + 1);
+    let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      (coord1.0, coord2.1),
+      font_size,
+      |style| {
+        self
+          .plotting_area
+          .draw(&Rectangle::new([coord1, coord2], style))
+      },
+    );
+
+    Some(r.map(|_| n))
+  }
+
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
+
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
+    }
+
+    Ok(())
+  }
+
+  fn tile_info(&self) -> std::string::String {
+    if self.tile.boustrophedon {
+      format!("tile = grid, W = {}, boustrophedon", self.tile.width)
+    } else {
+      format!("tile = grid, W = {}", self.tile.width)
+    }
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
+  }
+}
+
+/// Tallest image a grid is drawn in, in pixels.
+pub const MAX_HEIGHT: usize = 1 << 16;
+
+// Room the header, the chart margins and the tile's own padding take around
+// the cells, across and down.
+const PADDING: usize = MARGIN * 5 / 2;
+
+/// Height of the image, `image_size` wide, that a grid of a generator of
+/// `data_num` is drawn in. Cells are `cell=` pixels from `tp`, or as wide as
+/// the image allows, shrunk when needed so the image stays within
+/// `MAX_HEIGHT` but never below a pixel. The image grows down to hold the
+/// rows and is never shorter than it is wide.
+pub fn image_height(
+  tp: &str,
+  data_num: usize,
+  image_size: u32,
+) -> Result<u32, Box<dyn std::error::Error>> {
+  let (_, options) = tile_options(tp)?;
+  let mut width = default_width(data_num);
+  let mut cell = None;
+  for (key, value) in options {
+    match key {
+      "W" => width = parse_width(value)?,
+      "cell" => cell = Some(parse_cell(value)?),
+      _ => {}
+    }
+  }
+
+  let rows = rows(data_num, width);
+  let cell = cell.unwrap_or_else(|| {
+    let across = (image_size as usize).saturating_sub(PADDING) / width;
+    across.min(MAX_HEIGHT / rows).max(1)
+  });
+
+  let height = rows * cell + PADDING;
+  if height > MAX_HEIGHT {
+    return Err(
+      format!(
+        "Grid of {} rows is {} pixels tall, more than {}",
+        rows, height, MAX_HEIGHT
+      )
+      .into(),
+    );
+  }
+
+  Ok((height as u32).max(image_size))
+}
+
+// Width of a grid close to a square.
+fn default_width(data_num: usize) -> usize {
+  ((data_num + 1) as f64).sqrt().ceil() as usize
+}
+
+fn parse_width(value: &str) -> Result<usize, Box<dyn std::error::Error>> {
+  let width = value.parse()?;
+  if width == 0 {
+    return Err("W must be at least 1".into());
+  }
+
+  Ok(width)
+}
+
+fn parse_cell(value: &str) -> Result<usize, Box<dyn std::error::Error>> {
+  let cell = value.parse()?;
+  if cell == 0 {
+    return Err("cell must be at least 1".into());
+  }
+
+  Ok(cell)
+}
+
+// Rows needed for a generator of `data_num`, which yields one number more
+// as its window includes both ends.
+fn rows(data_num: usize, width: usize) -> usize {
+  (data_num + 1).div_ceil(width)
+}
+
+struct GridTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
+  width: usize,
+  boustrophedon: bool,
+  i: usize,
+}
+
+impl<'a, N: Number> GridTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>, width: usize) -> Self {
+    GridTile {
+      gen,
+      width,
+      boustrophedon: false,
+      i: 0,
+    }
+  }
+
+  fn next_with<T>(
+    &mut self,
+    next: impl FnOnce(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
+    if let Some((n, b)) = next(self.gen.as_mut()) {
+      let (row, mut col) = (self.i / self.width, self.i % self.width);
+      if self.boustrophedon && row % 2 == 1 {
+        col = self.width - 1 - col;
+      }
+
+      self.i += 1;
+      Some((n, col as isize, -(row as isize), b))
+    } else {
+      None
+    }
+  }
+}
+
+impl<'a, N: Number> Iterator for GridTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::ulam::generator::primes::PrimesGenerator;
+
+  #[test]
+  fn test_rows() {
+    // 1 to 60 fill 10 rows of 6 exactly, and 61 starts another.
+    assert_eq!(rows(PrimesGenerator::new(60, 1).data_num(), 6), 10);
+    assert_eq!(rows(PrimesGenerator::new(61, 1).data_num(), 6), 11);
+    assert_eq!(rows(PrimesGenerator::new(0, 0).data_num(), 6), 1);
+  }
+
+  #[test]
+  fn test_image_height() {
+    // A window that fits keeps the square image.
+    assert_eq!(image_height("0:W=30", 899, 800).unwrap(), 800);
+
+    // 1 to 1800 in rows of 30 keep the 20 pixel cells 800 pixels give.
+    assert_eq!(image_height("0:W=30", 1799, 800).unwrap(), 60 * 20 + 200);
+
+    // 10^6 numbers are 33,334 rows, drawn a pixel tall each.
+    let height = image_height("0:W=30", 999_999, 800).unwrap();
+    assert_eq!(height, 33_334 + 200);
+    assert!(height as usize <= MAX_HEIGHT);
+    assert_eq!(
+      image_height("0:W=30:cell=2", 899_999, 800).unwrap(),
+      60_000 + 200
+    );
+
+    assert!(image_height("0:W=30:cell=3", 999_999, 800).is_err());
+    assert!(image_height("0:W=1", 999_999, 800).is_err());
+    assert!(image_height("0:W=0", 99, 800).is_err());
+    assert!(image_height("0:cell=0", 99, 800).is_err());
+  }
+
+  #[test]
+  fn test_tile() {
+    let gen = PrimesGenerator::new(7, 1);
+    let points: Vec<_> = Grid::tile(Box::new(gen), 3).collect();
+    assert_eq!(
+      points,
+      vec![
+        (1, 0, 0, false),
+        (2, 1, 0, true),
+        (3, 2, 0, true),
+        (4, 0, -1, false),
+        (5, 1, -1, true),
+        (6, 2, -1, false),
+        (7, 0, -2, true)
+      ]
+    );
+
+    let gen = PrimesGenerator::new(7, 1);
+    let mut ite = Grid::tile(Box::new(gen), 3);
+    ite.boustrophedon = true;
+    let points: Vec<_> = ite.map(|(n, x, y, _)| (n, x, y)).collect();
+    assert_eq!(
+      points,
+      vec![
+        (1, 0, 0),
+        (2, 1, 0),
+        (3, 2, 0),
+        (4, 2, -1),
+        (5, 1, -1),
+        (6, 0, -1),
+        (7, 0, -2)
+      ]
+    );
+  }
+}
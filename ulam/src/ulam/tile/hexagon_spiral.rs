@@ -1,160 +1,267 @@
 use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::skip_to_start;
+use crate::ulam::tile::tile::tile_options;
 use crate::ulam::tile::tile::Tile;
 use crate::ulam::tile::tile::MARGIN;
 use crate::ulam::tile::types::LabelMode;
+use crate::ulam::tile::types::Turn;
 use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
 use plotters::prelude::BitMapBackend;
 use plotters::prelude::Cartesian2d;
 use plotters::prelude::DrawingArea;
+use plotters::prelude::RGBColor;
 use plotters::prelude::Rectangle;
-use plotters::prelude::ShapeStyle;
-use plotters::prelude::Text;
-use plotters::prelude::TextStyle;
-use plotters::prelude::BLACK;
-use plotters::prelude::RED;
-use plotters::style::IntoFont;
-
-pub struct HexagonSpiral<'a, 'b> {
+
+pub struct HexagonSpiral<'a, 'b, N = usize> {
   plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-  tile: HexagonSpiralTile<'a>,
+  tile: HexagonSpiralTile<'a, N>,
   block: f64,
   label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
 }
 
-impl<'a, 'b> HexagonSpiral<'a, 'b> {
+impl<'a, 'b, N: Number> HexagonSpiral<'a, 'b, N> {
   pub fn new(
-    gen: Box<dyn Generator + 'a>,
+    gen: Box<dyn Generator<N> + 'a>,
     plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-  ) -> HexagonSpiral<'a, 'b> {
-    let n = gen.data_num();
-    let mut m = 0;
-    let mut cycle = 1;
-    loop {
-      if m < n {
-        m += 6 * cycle;
-        cycle += 1;
-      } else {
-        break;
-      }
-    }
-
-    let vw = cycle as f64 * 2f64;
+  ) -> HexagonSpiral<'a, 'b, N> {
+    let heat_map = gen.value_range().map(HeatMap::new);
+    Self::with_heat_map(gen, plotting_area, heat_map)
+  }
 
-    let range = plotting_area.get_pixel_range().0;
-    let block = (range.end - range.start - MARGIN as i32) as f64 / vw;
+  /// Hexagon spiral coloured from `heat_map` when given. The eisenstein
+  /// lattice gives none, so building it never reads the generator ahead.
+  pub(crate) fn with_heat_map(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    heat_map: Option<HeatMap>,
+  ) -> HexagonSpiral<'a, 'b, N> {
+    let n = gen.data_num();
+    let categories = gen.categories();
     HexagonSpiral {
       plotting_area,
       tile: HexagonSpiral::tile(gen),
-      block,
+      block: Self::block(plotting_area, n),
       label_mode: LabelMode::None,
+      categories,
+      heat_map,
     }
   }
 
   pub fn from_tp(
     tp: &str,
-    gen: Box<dyn Generator + 'a>,
+    gen: Box<dyn Generator<N> + 'a>,
     plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
   ) -> Result<Self, Box<dyn std::error::Error>> {
-    let mut tile = Self::new(gen, plotting_area);
+    let heat_map = gen.value_range().map(HeatMap::new);
+    Self::from_tp_with_heat_map(tp, gen, plotting_area, heat_map)
+  }
 
-    let mut tp = tp.split(":");
-    let label_mode = tp.next();
+  /// Like `from_tp`, but with the given `heat_map`. The six `dir`s run
+  /// from `right` round to `down-right`.
+  pub(crate) fn from_tp_with_heat_map(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    heat_map: Option<HeatMap>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::with_heat_map(gen, plotting_area, heat_map);
 
-    let label_mode: usize = if let Some(from) = label_mode {
-      from.parse()?
-    } else {
-      0
-    };
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      match key {
+        "turn" => tile.tile.turn = value.parse()?,
+        "dir" => {
+          tile.tile.dir = match value {
+            "right" => 0,
+            "up-right" => 1,
+            "up-left" => 2,
+            "left" => 3,
+            "down-left" => 4,
+            "down-right" => 5,
+            _ => return Err(format!("Unknown direction({})", value).into()),
+          }
+        }
+        "start" => tile.tile.start = Some(N::parse(value)?),
+        _ => heat_map_option(tile.heat_map.as_mut(), key, value)?,
+      }
+    }
 
-    tile.label_mode = LabelMode::from(label_mode);
     Ok(tile)
   }
 
-  fn tile(gen: Box<dyn Generator + 'a>) -> HexagonSpiralTile<'a> {
+  fn tile(gen: Box<dyn Generator<N> + 'a>) -> HexagonSpiralTile<'a, N> {
     HexagonSpiralTile::new(gen)
   }
 
+  // Size of a cell when the spiral runs n cells out from the centre.
+  fn block(
+    plotting_area: &DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    n: usize,
+  ) -> f64 {
+    let mut m = 0;
+    let mut cycle = 1;
+    loop {
+      if m < n {
+        m += 6 * cycle;
+        cycle += 1;
+      } else {
+        break;
+      }
+    }
+
+    let vw = cycle as f64 * 2f64;
+
+    let range = plotting_area.get_pixel_range().0;
+    (range.end - range.start - MARGIN as i32) as f64 / vw
+  }
+
   fn normalize(&self, x: isize, y: isize) -> (f64, f64) {
     let x = x as f64 * self.block / 2.0;
     let y = y as f64 * self.block;
 
     (x, y)
   }
+
+  /// Draws the next number like `draw_next`, in the colour `color` gives its
+  /// lattice point rather than by the generator.
+  pub(crate) fn draw_next_by(
+    &mut self,
+    color: impl Fn(isize, isize) -> Option<RGBColor>,
+  ) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let next = self.tile.next_with(|gen| gen.next());
+    self.draw(next.map(|(n, x, y, _)| (n, x, y, color(x, y))))
+  }
+
+  // Draws the cell of a number, given with its lattice point and colour.
+  fn draw(
+    &mut self,
+    next: Option<(N, isize, isize, Option<RGBColor>)>,
+  ) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    if let Some(last) = self.tile.last.take() {
+      self.block = Self::block(self.plotting_area, last);
+    }
+
+    let (n, x, y, color) = next?;
+    let coord1 = self.normalize(x, y);
+    let coord2 = self.normalize(x + 2, y + 1);
+    let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      (coord1.0, coord2.1),
+      font_size,
+      |style| {
+        self
+          .plotting_area
+          .draw(&Rectangle::new([coord1, coord2], style))
+      },
+    );
+
+    Some(r.map(|_| n))
+  }
 }
 
-impl<'a, 'b> Tile for HexagonSpiral<'a, 'b> {
-  fn draw_next(&mut self) -> Option<Result<usize, Box<dyn std::error::Error>>> {
-    if let Some((n, x, y, b)) = self.tile.next() {
-      let coord1 = self.normalize(x, y);
-      let coord2 = self.normalize(x + 2, y + 1);
-
-      if b {
-        let mut r = self.plotting_area.draw(&Rectangle::new(
-          [coord1, coord2],
-          Into::<ShapeStyle>::into(&RED).filled(),
-        ));
-
-        if let Err(err) = r {
-          return Some(Err(Box::new(err)));
-        };
-
-        if self.label_mode == LabelMode::All || self.label_mode == LabelMode::OnlyPositive {
-          let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
-          let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
-          r = self
-            .plotting_area
-            .draw(&Text::new(n.to_string(), (coord1.0, coord2.1), &style));
-        }
+impl<'a, 'b, N: Number> Tile<N> for HexagonSpiral<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+    self.draw(next)
+  }
 
-        return match r {
-          Ok(_) => Some(Ok(n)),
-          Err(err) => Some(Err(Box::new(err))),
-        };
-      } else {
-        if self.label_mode == LabelMode::All || self.label_mode == LabelMode::OnlyNegative {
-          let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
-          let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
-          let r = self
-            .plotting_area
-            .draw(&Text::new(n.to_string(), (coord1.0, coord2.1), &style));
-
-          return match r {
-            Ok(_) => Some(Ok(n)),
-            Err(err) => Some(Err(Box::new(err))),
-          };
-        }
-        Some(Ok(n))
-      }
-    } else {
-      return None;
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
     }
+
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
+    }
+
+    Ok(())
   }
 
   fn tile_info(&self) -> std::string::String {
-    format!("tile = spiral6")
+    match &self.tile.start {
+      Some(start) => format!("tile = spiral6, start = {}", start),
+      None => "tile = spiral6".to_string(),
+    }
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
   }
 }
 
-struct HexagonSpiralTile<'a> {
-  gen: Box<dyn Generator + 'a>,
+struct HexagonSpiralTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
   transit_info: (usize, isize, isize),
-  prev: (usize, isize, isize, bool),
+  prev: Option<(isize, isize)>,
+  dir: usize,
+  turn: Turn,
+  start: Option<N>,
+  last: Option<usize>,
 }
 
-impl<'a> HexagonSpiralTile<'a> {
-  pub fn new(gen: Box<dyn Generator + 'a>) -> Self {
+impl<'a, N: Number> HexagonSpiralTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>) -> Self {
     HexagonSpiralTile {
       gen,
-      transit_info: (0, 2, 1), // (dir, rest, step)
-      prev: (0, -2, 0, false),
+      transit_info: (0, 1, 1), // (leg, rest, step)
+      prev: None,
+      dir: 0,
+      turn: Turn::Counterclockwise,
+      start: None,
+      last: None,
     }
   }
-}
 
-impl<'a> Iterator for HexagonSpiralTile<'a> {
-  type Item = (usize, isize, isize, bool);
+  fn next_with<T>(
+    &mut self,
+    mut next: impl FnMut(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
+    let mut first = next(self.gen.as_mut())?;
 
-  fn next(&mut self) -> std::option::Option<Self::Item> {
+    if let (None, Some(start)) = (self.prev, self.start.clone()) {
+      let (at_start, offset, last) = skip_to_start(self.gen.as_mut(), &mut next, first, start)?;
+      first = at_start;
+      for _ in 0..offset {
+        self.step();
+      }
+      self.last = Some(last);
+    }
+
+    let (n, b) = first;
+    let (x, y) = self.step();
+    Some((n, x, y, b))
+  }
+
+  fn step(&mut self) -> (isize, isize) {
     // 0: right, 1: up-right, 2: up-left, 3: left, 4: down-left, 5: down-right
 
     //  r 1 ( 2, 0)
@@ -169,52 +276,65 @@ impl<'a> Iterator for HexagonSpiralTile<'a> {
     //  l 2 (-2, 0)
     // dl 2 (-1, -1)
     // dr 2 ( 1, -1)
+    //
+    // Legs are counted from the initial direction in the way the spiral
+    // turns, so the table above is the counter-clockwise spiral going right.
 
-    if let Some((n, b)) = self.gen.next() {
-      let (dir, mut rest, step) = self.transit_info;
-      let (_, x, y, _) = self.prev;
-      let ret = if dir == 0 {
-        (n, x + 2, y, b)
-      } else if dir == 1 {
-        (n, x + 1, y + 1, b)
-      } else if dir == 2 {
-        (n, x - 1, y + 1, b)
-      } else if dir == 3 {
-        (n, x - 2, y, b)
-      } else if dir == 4 {
-        (n, x - 1, y - 1, b)
-      } else {
-        (n, x + 1, y - 1, b)
-      };
-
-      self.prev = ret;
-      rest -= 1;
-      if rest == 0 {
-        if dir == 0 {
-          if step == 1 {
-            self.transit_info = (2, step, step);
-          } else {
-            self.transit_info = (1, step - 1, step);
-          }
-        } else if dir == 1 {
+    let (x, y) = match self.prev {
+      Some(prev) => prev,
+      None => {
+        self.transit_info = (0, 1, 1);
+        self.prev = Some((0, 0));
+        return (0, 0);
+      }
+    };
+
+    let (leg, mut rest, step) = self.transit_info;
+    let dir = match self.turn {
+      Turn::Counterclockwise => (self.dir + leg) % 6,
+      Turn::Clockwise => (self.dir + 6 - leg) % 6,
+    };
+    let ret = if dir == 0 {
+      (x + 2, y)
+    } else if dir == 1 {
+      (x + 1, y + 1)
+    } else if dir == 2 {
+      (x - 1, y + 1)
+    } else if dir == 3 {
+      (x - 2, y)
+    } else if dir == 4 {
+      (x - 1, y - 1)
+    } else {
+      (x + 1, y - 1)
+    };
+
+    self.prev = Some(ret);
+    rest -= 1;
+    if rest == 0 {
+      if leg == 0 {
+        if step == 1 {
           self.transit_info = (2, step, step);
-        } else if dir == 2 {
-          self.transit_info = (3, step, step);
-        } else if dir == 3 {
-          self.transit_info = (4, step, step);
-        } else if dir == 4 {
-          self.transit_info = (5, step, step);
         } else {
-          self.transit_info = (0, step + 1, step + 1);
+          self.transit_info = (1, step - 1, step);
         }
+      } else if leg == 5 {
+        self.transit_info = (0, step + 1, step + 1);
       } else {
-        self.transit_info = (dir, rest, step);
+        self.transit_info = (leg + 1, step, step);
       }
-
-      return Some(ret);
     } else {
-      return None;
+      self.transit_info = (leg, rest, step);
     }
+
+    ret
+  }
+}
+
+impl<'a, N: Number> Iterator for HexagonSpiralTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
   }
 }
 
@@ -242,4 +362,34 @@ mod tests {
     assert_eq!(ite.next(), Some((10, 2, 2, false)));
     assert_eq!(ite.next(), None);
   }
+
+  #[test]
+  fn test_tile_options() {
+    // The spiral above mirrored in the x-axis.
+    let gen = PrimesGenerator::new(10, 0);
+    let mut ite = HexagonSpiral::tile(Box::new(gen));
+    ite.turn = Turn::Clockwise;
+
+    assert_eq!(ite.next(), Some((0, 0, 0, false)));
+    assert_eq!(ite.next(), Some((1, 2, 0, false)));
+    assert_eq!(ite.next(), Some((2, 1, -1, true)));
+    assert_eq!(ite.next(), Some((3, -1, -1, true)));
+    assert_eq!(ite.next(), Some((4, -2, 0, false)));
+    assert_eq!(ite.next(), Some((5, -1, 1, true)));
+    assert_eq!(ite.next(), Some((6, 1, 1, false)));
+    assert_eq!(ite.next(), Some((7, 3, 1, true)));
+    assert_eq!(ite.next(), Some((8, 4, 0, false)));
+    assert_eq!(ite.next(), Some((9, 3, -1, false)));
+    assert_eq!(ite.next(), Some((10, 2, -2, false)));
+
+    // The spiral above turned a third of the way round.
+    let gen = PrimesGenerator::new(4, 2);
+    let mut ite = HexagonSpiral::tile(Box::new(gen));
+    ite.dir = 2;
+    ite.start = Some(0);
+    assert_eq!(ite.next(), Some((2, -2, 0, true)));
+    assert_eq!(ite.last, Some(4));
+    assert_eq!(ite.next(), Some((3, -1, -1, true)));
+    assert_eq!(ite.next(), Some((4, 1, -1, false)));
+  }
 }
@@ -0,0 +1,238 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::tile_options;
+use crate::ulam::tile::tile::Tile;
+use crate::ulam::tile::tile::MARGIN;
+use crate::ulam::tile::triangle_spiral::triangle;
+use crate::ulam::tile::triangle_spiral::ROW_HEIGHT;
+use crate::ulam::tile::types::LabelMode;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::Polygon;
+
+/// Klauber's triangle: row k holds (k - 1)² + 1 to k² from left to right,
+/// so the squares run down the right edge and n² - n + 1 down the middle.
+pub struct KlauberTriangle<'a, 'b, N = usize> {
+  plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  tile: KlauberTriangleTile<'a, N>,
+  layout: Option<(f64, f64)>,
+  label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
+}
+
+impl<'a, 'b, N: Number> KlauberTriangle<'a, 'b, N> {
+  pub fn new(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> KlauberTriangle<'a, 'b, N> {
+    let categories = gen.categories();
+    let heat_map = gen.value_range().map(HeatMap::new);
+    KlauberTriangle {
+      plotting_area,
+      tile: KlauberTriangle::tile(gen),
+      layout: None,
+      label_mode: LabelMode::None,
+      categories,
+      heat_map,
+    }
+  }
+
+  pub fn from_tp(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::new(gen, plotting_area);
+
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      heat_map_option(tile.heat_map.as_mut(), key, value)?;
+    }
+
+    Ok(tile)
+  }
+
+  fn tile(gen: Box<dyn Generator<N> + 'a>) -> KlauberTriangleTile<'a, N> {
+    KlauberTriangleTile::new(gen)
+  }
+
+  // Block size and vertical centre. The rows drawn run from that of the
+  // first n to that of the last, which is known once the first n is seen.
+  fn layout(&mut self, n: &N) -> (f64, f64) {
+    let first = row(n);
+    let last = row(&(n.clone() + N::from_usize(self.tile.gen.data_num())));
+    let range = self.plotting_area.get_pixel_range().0;
+    let block = (range.end - range.start - MARGIN as i32) as f64 / last.max(1) as f64;
+    // Row k is drawn as row 1 - k.
+    let center = (3 - first as isize - last as isize) as f64 / 2.0 * ROW_HEIGHT;
+    *self.layout.get_or_insert((block, center))
+  }
+}
+
+impl<'a, 'b, N: Number> Tile<N> for KlauberTriangle<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+
+    let (n, x, y, color) = next?;
+    let (block, center) = self.layout(&n);
+    let normalize = |(x, y): (f64, f64)| (x * block, (y - center) * block);
+    let points: Vec<_> = triangle(x, y).iter().map(|&p| normalize(p)).collect();
+    let label = normalize((x as f64 / 2.0 - 0.25, (y as f64 + 0.6) * ROW_HEIGHT));
+    // Triangles are half a block wide on average.
+    let font_size = (block / 4.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      label,
+      font_size,
+      |style| self.plotting_area.draw(&Polygon::new(points, style)),
+    );
+
+    Some(r.map(|_| n))
+  }
+
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
+
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
+    }
+
+    Ok(())
+  }
+
+  fn tile_info(&self) -> std::string::String {
+    "tile = klauber".to_string()
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
+  }
+}
+
+// Row of n, ⌈√n⌉, with 0 in row 0.
+fn row<N: Number>(n: &N) -> usize {
+  let zero = N::from_usize(0);
+  if *n == zero {
+    return 0;
+  }
+
+  (n.clone() - N::from_usize(1))
+    .isqrt()
+    .to_usize()
+    .map_or(usize::MAX, |r| r + 1)
+}
+
+// Cell of n, on the row 1 - k for row k. Row k has 2k - 1 triangles,
+// centred on x = 0; 0 sits just above the apex.
+fn cell<N: Number>(n: &N) -> (isize, isize) {
+  let k = row(n);
+  if k == 0 {
+    return (0, 1);
+  }
+
+  let r = N::from_usize(k - 1);
+  let col = (n.clone() - N::from_usize(1) - r.clone() * r)
+    .to_usize()
+    .unwrap_or(0);
+  (col as isize - (k as isize - 1), 1 - k as isize)
+}
+
+struct KlauberTriangleTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
+}
+
+impl<'a, N: Number> KlauberTriangleTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>) -> Self {
+    KlauberTriangleTile { gen }
+  }
+
+  fn next_with<T>(
+    &mut self,
+    next: impl FnOnce(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
+    next(self.gen.as_mut()).map(|(n, b)| {
+      let (x, y) = cell(&n);
+      (n, x, y, b)
+    })
+  }
+}
+
+impl<'a, N: Number> Iterator for KlauberTriangleTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::ulam::generator::primes::PrimesGenerator;
+
+  #[test]
+  fn test_tile() {
+    let gen = PrimesGenerator::new(10, 1);
+    let mut ite = KlauberTriangle::tile(Box::new(gen));
+
+    assert_eq!(ite.next(), Some((1, 0, 0, false)));
+    assert_eq!(ite.next(), Some((2, -1, -1, true)));
+    assert_eq!(ite.next(), Some((3, 0, -1, true)));
+    assert_eq!(ite.next(), Some((4, 1, -1, false)));
+    assert_eq!(ite.next(), Some((5, -2, -2, true)));
+    assert_eq!(ite.next(), Some((6, -1, -2, false)));
+    assert_eq!(ite.next(), Some((7, 0, -2, true)));
+    assert_eq!(ite.next(), Some((8, 1, -2, false)));
+    assert_eq!(ite.next(), Some((9, 2, -2, false)));
+    assert_eq!(ite.next(), Some((10, -3, -3, false)));
+    assert_eq!(ite.next(), None);
+  }
+
+  #[test]
+  fn test_tile_from() {
+    // Cells depend on n, not on where the window starts.
+    let gen = PrimesGenerator::new(12, 5);
+    let mut ite = KlauberTriangle::tile(Box::new(gen));
+
+    assert_eq!(ite.next(), Some((5, -2, -2, true)));
+    assert_eq!(ite.next(), Some((6, -1, -2, false)));
+    assert_eq!(ite.nth(3), Some((10, -3, -3, false)));
+    assert_eq!(ite.nth(1), Some((12, -1, -3, false)));
+    assert_eq!(ite.next(), None);
+
+    assert_eq!(cell(&0usize), (0, 1));
+    assert_eq!(cell(&1usize), (0, 0));
+    assert_eq!(cell(&(1u128 << 100)), ((1 << 50) - 1, 1 - (1 << 50)));
+  }
+}
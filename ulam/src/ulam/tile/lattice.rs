@@ -0,0 +1,250 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::generator::primality::is_eisenstein_prime;
+use crate::ulam::generator::primality::is_gaussian_prime;
+use crate::ulam::tile::hexagon_spiral::HexagonSpiral;
+use crate::ulam::tile::square_spiral::SquareSpiral;
+use crate::ulam::tile::tile::Tile;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::RED;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Ring {
+  /// Gaussian integers x + yi at the point (x, y) of the square spiral.
+  Gaussian,
+  /// Eisenstein integers on the hexagon spiral. The point (x, y) in its
+  /// doubled-x coordinates is a + bω with a = (x + y) / 2 and b = y, so
+  /// (2, 0) is 1 and (-1, 1) is ω.
+  Eisenstein,
+}
+
+impl Ring {
+  pub fn from_name(name: &str) -> Option<Ring> {
+    match name {
+      "gaussian" => Some(Ring::Gaussian),
+      "eisenstein" => Some(Ring::Eisenstein),
+      _ => None,
+    }
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      Ring::Gaussian => "gaussian",
+      Ring::Eisenstein => "eisenstein",
+    }
+  }
+
+  /// Whether the lattice point (x, y) is a prime of the ring.
+  pub fn is_prime(&self, x: isize, y: isize) -> bool {
+    match self {
+      Ring::Gaussian => is_gaussian_prime(x, y),
+      Ring::Eisenstein => is_eisenstein_prime((x + y) / 2, y),
+    }
+  }
+}
+
+enum Spiral<'a, 'b, N> {
+  Square(SquareSpiral<'a, 'b, N>),
+  Hexagon(HexagonSpiral<'a, 'b, N>),
+}
+
+/// Marks the primes of a ring at the lattice points of the spiral that fits
+/// it. A cell is marked by its lattice point alone, so the tile takes only
+/// the range of n to walk and no generator.
+pub struct Lattice<'a, 'b, N = usize> {
+  spiral: Spiral<'a, 'b, N>,
+  ring: Ring,
+}
+
+impl<'a, 'b, N: Number + 'a> Lattice<'a, 'b, N> {
+  pub fn new(
+    from: N,
+    to: N,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    ring: Ring,
+  ) -> Lattice<'a, 'b, N> {
+    let numbers = Box::new(Numbers::new(from, to));
+    let spiral = match ring {
+      Ring::Gaussian => Spiral::Square(SquareSpiral::with_heat_map(numbers, plotting_area, None)),
+      Ring::Eisenstein => {
+        Spiral::Hexagon(HexagonSpiral::with_heat_map(numbers, plotting_area, None))
+      }
+    };
+
+    Lattice { spiral, ring }
+  }
+
+  /// `gp` is the range `from:to` of `--gp`, and `tp` takes the options of
+  /// the spiral.
+  pub fn from_tp(
+    tp: &str,
+    gp: &str,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    ring: Ring,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let numbers = Box::new(Numbers::from_gp(gp)?);
+    let spiral = match ring {
+      Ring::Gaussian => Spiral::Square(SquareSpiral::from_tp_with_heat_map(
+        tp,
+        numbers,
+        plotting_area,
+        None,
+      )?),
+      Ring::Eisenstein => Spiral::Hexagon(HexagonSpiral::from_tp_with_heat_map(
+        tp,
+        numbers,
+        plotting_area,
+        None,
+      )?),
+    };
+
+    Ok(Lattice { spiral, ring })
+  }
+}
+
+impl<'a, 'b, N: Number> Tile<N> for Lattice<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let ring = self.ring;
+    let color = |x, y| ring.is_prime(x, y).then_some(RED);
+    match &mut self.spiral {
+      Spiral::Square(spiral) => spiral.draw_next_by(color),
+      Spiral::Hexagon(spiral) => spiral.draw_next_by(color),
+    }
+  }
+
+  fn draw_legend(
+    &self,
+    _area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+  }
+
+  fn draw_color_bar(
+    &self,
+    _area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+  }
+
+  fn tile_info(&self) -> std::string::String {
+    format!("tile = {}", self.ring.name())
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    match &self.spiral {
+      Spiral::Square(spiral) => spiral.generator_info(),
+      Spiral::Hexagon(spiral) => spiral.generator_info(),
+    }
+  }
+}
+
+/// The numbers of a range, each yielded as is. The spiral walks its cells
+/// by what a generator yields, and this one computes nothing for them.
+struct Numbers<N> {
+  i: N,
+  max: N,
+  skip: N,
+}
+
+impl<N: Number> Numbers<N> {
+  fn new(from: N, to: N) -> Self {
+    Numbers {
+      i: from.clone(),
+      max: to,
+      skip: from,
+    }
+  }
+
+  fn from_gp(gp: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut gp = gp.split(":");
+    let from = gp.next();
+    let to = gp.next();
+
+    let from: N = if let Some(from) = from {
+      N::parse(from)?
+    } else {
+      N::from_usize(1)
+    };
+
+    let to: N = if let Some(to) = to {
+      N::parse(to)?
+    } else {
+      N::from_usize(1000)
+    };
+
+    Ok(Self::new(from, to))
+  }
+}
+
+impl<N: Number> Generator<N> for Numbers<N> {
+  fn data_num(&self) -> usize {
+    if self.max < self.skip {
+      return 0;
+    }
+
+    (self.max.clone() - self.skip.clone())
+      .to_usize()
+      .unwrap_or(usize::MAX)
+  }
+
+  fn next(&mut self) -> Option<(N, bool)> {
+    if self.i > self.max {
+      return None;
+    }
+
+    let n = self.i.clone();
+    self.i = n.clone() + N::from_usize(1);
+    Some((n, false))
+  }
+
+  fn generator_info(&self) -> String {
+    format!("from = {}, to = {}", self.skip, self.max)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_gaussian() {
+    // The first cells of the square spiral: 0, 1, 1 + i, i, -1 + i, -1.
+    let points = [(0, 0), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0)];
+    let marked: Vec<_> = points
+      .iter()
+      .map(|&(x, y)| Ring::Gaussian.is_prime(x, y))
+      .collect();
+    assert_eq!(marked, vec![false, false, true, false, true, false]);
+
+    // 3 stays prime, 2 = -i(1 + i)^2 and 5 = (2 + i)(2 - i) do not.
+    assert!(Ring::Gaussian.is_prime(3, 0));
+    assert!(!Ring::Gaussian.is_prime(2, 0));
+    assert!(!Ring::Gaussian.is_prime(5, 0));
+    assert!(Ring::Gaussian.is_prime(2, 1));
+  }
+
+  #[test]
+  fn test_eisenstein() {
+    // The first ring of the hexagon spiral holds the units.
+    let ring = [(2, 0), (1, 1), (-1, 1), (-2, 0), (-1, -1), (1, -1)];
+    assert!(ring.iter().all(|&(x, y)| !Ring::Eisenstein.is_prime(x, y)));
+
+    // 2 stays prime, 3 = -ω^2(1 - ω)^2 does not, and 2 + ω has norm 3.
+    assert!(Ring::Eisenstein.is_prime(4, 0));
+    assert!(!Ring::Eisenstein.is_prime(6, 0));
+    assert!(Ring::Eisenstein.is_prime(3, 1));
+  }
+
+  #[test]
+  fn test_numbers() {
+    let mut numbers = Numbers::<usize>::from_gp("3:6").unwrap();
+    assert_eq!(numbers.data_num(), 3);
+    let all: Vec<_> = std::iter::from_fn(|| numbers.next()).collect();
+    assert_eq!(all, vec![(3, false), (4, false), (5, false), (6, false)]);
+    assert_eq!(numbers.generator_info(), "from = 3, to = 6");
+  }
+}
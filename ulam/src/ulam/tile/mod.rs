@@ -1,5 +1,15 @@
+pub mod colormap;
+pub mod gosper_curve;
+pub mod grid;
 pub mod hexagon_spiral;
+pub mod klauber_triangle;
+pub mod lattice;
+pub mod palette;
+pub mod sacks_spiral;
+pub mod square_curve;
 pub mod square_spiral;
 pub mod square_zigzag;
+#[allow(clippy::module_inception)]
 pub mod tile;
+pub mod triangle_spiral;
 pub mod types;
@@ -0,0 +1,122 @@
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::HSLColor;
+use plotters::prelude::RGBColor;
+use plotters::prelude::Rectangle;
+use plotters::prelude::ShapeStyle;
+use plotters::prelude::TextStyle;
+use plotters::prelude::BLACK;
+use plotters::prelude::RED;
+use plotters::style::Color;
+use plotters::style::IntoFont;
+
+const CATEGORY_COLORS: [RGBColor; 10] = [
+  RGBColor(31, 119, 180),
+  RGBColor(255, 127, 14),
+  RGBColor(44, 160, 44),
+  RGBColor(214, 39, 40),
+  RGBColor(148, 103, 189),
+  RGBColor(140, 86, 75),
+  RGBColor(227, 119, 194),
+  RGBColor(127, 127, 127),
+  RGBColor(188, 189, 34),
+  RGBColor(23, 190, 207),
+];
+
+/// Colour of a non-zero category. A generator with a single drawn category
+/// keeps the plain red. Past the fixed palette, hues are a golden angle apart
+/// and the lightness cycles, so no two categories share a colour.
+pub fn category_color(category: usize, categories: usize) -> RGBColor {
+  if categories <= 2 {
+    return RED;
+  }
+
+  if let Some(&color) = CATEGORY_COLORS.get(category - 1) {
+    return color;
+  }
+
+  let i = category - 1 - CATEGORY_COLORS.len();
+  let hue = (i as f64 * 0.618_033_988_749_895).fract();
+  let lightness = [0.45, 0.6, 0.75][i / 7 % 3];
+  let (r, g, b) = HSLColor(hue, 0.7, lightness).rgb();
+  RGBColor(r, g, b)
+}
+
+/// Draws one swatch per drawn category, right aligned at the bottom of `area`,
+/// the strip below the tiles that nothing else is drawn in. Swatches that do
+/// not fit across it go on rows above, in a smaller font when the rows would
+/// not fit either.
+/// Nothing is drawn for a generator with a single drawn category.
+pub fn draw_legend(
+  area: &DrawingArea<BitMapBackend, Shift>,
+  categories: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+  if categories.len() <= 2 {
+    return Ok(());
+  }
+
+  let (width, height) = area.dim_in_pixel();
+  let max_width = width as i32 - 20;
+
+  let mut layout = None;
+  for font_size in [16, 13, 10] {
+    let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
+    let swatch = font_size - 2;
+
+    let mut rows: Vec<Vec<(usize, i32)>> = vec![vec![]];
+    for (i, label) in categories.iter().enumerate().skip(1) {
+      let w = area.estimate_text_size(label, &style)?.0 as i32 + swatch + 16;
+      let row = rows.last_mut().unwrap();
+      if !row.is_empty() && row.iter().map(|&(_, w)| w).sum::<i32>() + w > max_width {
+        rows.push(vec![(i, w)]);
+      } else {
+        row.push((i, w));
+      }
+    }
+
+    let fits = rows.len() as i32 * (swatch + 4) + 4 <= height as i32;
+    layout = Some((style, swatch, rows));
+    if fits {
+      break;
+    }
+  }
+
+  let (style, swatch, rows) = layout.unwrap();
+  let bottom = height as i32 - swatch - 4;
+  for (r, row) in rows.iter().enumerate() {
+    let y = bottom - (rows.len() - 1 - r) as i32 * (swatch + 4);
+    let mut x = (width as i32 - row.iter().map(|&(_, w)| w).sum::<i32>() - 10).max(0);
+    for &(i, w) in row {
+      area.draw(&Rectangle::new(
+        [(x, y), (x + swatch, y + swatch)],
+        Into::<ShapeStyle>::into(&category_color(i, categories.len())).filled(),
+      ))?;
+      area.draw_text(&categories[i], &style, (x + swatch + 4, y - 1))?;
+      x += w;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_category_color() {
+    assert_eq!(category_color(1, 2), RED);
+
+    let categories = 64;
+    let colors: Vec<_> = (1..categories)
+      .map(|c| category_color(c, categories))
+      .collect();
+    assert_eq!(colors[0], CATEGORY_COLORS[0]);
+    for (i, a) in colors.iter().enumerate() {
+      for b in &colors[i + 1..] {
+        assert_ne!(a, b);
+      }
+    }
+  }
+}
@@ -0,0 +1,198 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::tile_options;
+use crate::ulam::tile::tile::Tile;
+use crate::ulam::tile::tile::MARGIN;
+use crate::ulam::tile::types::LabelMode;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::Circle;
+use plotters::prelude::DrawingArea;
+use std::f64::consts::PI;
+
+/// Sacks spiral: n sits at polar coordinates (√n, 2π√n), so each turn ends
+/// on a perfect square on the positive x-axis.
+///
+/// Turns are one unit apart and consecutive numbers about π apart along a
+/// turn, so each number is drawn as a disc of half a unit.
+pub struct SacksSpiral<'a, 'b, N = usize> {
+  plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  tile: SacksSpiralTile<'a, N>,
+  radius: f64,
+  scale: Option<f64>,
+  label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
+}
+
+impl<'a, 'b, N: Number> SacksSpiral<'a, 'b, N> {
+  pub fn new(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> SacksSpiral<'a, 'b, N> {
+    let categories = gen.categories();
+    let heat_map = gen.value_range().map(HeatMap::new);
+    let range = plotting_area.get_pixel_range().0;
+    let radius = (range.end - range.start - MARGIN as i32) as f64 / 2.0;
+    SacksSpiral {
+      plotting_area,
+      tile: SacksSpiral::tile(gen),
+      radius,
+      scale: None,
+      label_mode: LabelMode::None,
+      categories,
+      heat_map,
+    }
+  }
+
+  pub fn from_tp(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::new(gen, plotting_area);
+
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      heat_map_option(tile.heat_map.as_mut(), key, value)?;
+    }
+
+    Ok(tile)
+  }
+
+  fn tile(gen: Box<dyn Generator<N> + 'a>) -> SacksSpiralTile<'a, N> {
+    SacksSpiralTile::new(gen)
+  }
+
+  // Pixels per unit. The outermost turn, that of the last n, touches the
+  // edge of the plotting area; it is known once the first n is seen.
+  fn scale(&mut self, n: &N) -> f64 {
+    let last = n.to_f64() + self.tile.gen.data_num() as f64;
+    let radius = self.radius;
+    *self
+      .scale
+      .get_or_insert_with(|| radius / last.sqrt().max(1.0))
+  }
+}
+
+impl<'a, 'b, N: Number> Tile<N> for SacksSpiral<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+
+    let (n, x, y, color) = next?;
+    let scale = self.scale(&n);
+    let coord = (x * scale, y * scale);
+    let font_size = (scale / 2.0).min(MARGIN as f64).max(8.0);
+    let size = (scale / 2.0).max(1.0) as u32;
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      coord,
+      font_size,
+      |style| self.plotting_area.draw(&Circle::new(coord, size, style)),
+    );
+
+    Some(r.map(|_| n))
+  }
+
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
+
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
+    }
+
+    Ok(())
+  }
+
+  fn tile_info(&self) -> std::string::String {
+    "tile = sacks".to_string()
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
+  }
+}
+
+struct SacksSpiralTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
+}
+
+impl<'a, N: Number> SacksSpiralTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>) -> Self {
+    SacksSpiralTile { gen }
+  }
+
+  fn next_with<T>(
+    &mut self,
+    next: impl FnOnce(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, f64, f64, T)> {
+    next(self.gen.as_mut()).map(|(n, b)| {
+      let r = n.to_f64().sqrt();
+      let theta = 2.0 * PI * r;
+      (n, r * theta.cos(), r * theta.sin(), b)
+    })
+  }
+}
+
+impl<'a, N: Number> Iterator for SacksSpiralTile<'a, N> {
+  type Item = (N, f64, f64, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::ulam::generator::primes::PrimesGenerator;
+
+  #[test]
+  fn test_tile() {
+    let gen = PrimesGenerator::new(16, 0);
+    let points: Vec<_> = SacksSpiral::tile(Box::new(gen)).collect();
+    assert_eq!(points.len(), 17);
+
+    // Perfect squares sit on the positive x-axis, one unit per turn.
+    for k in 0..=4 {
+      let (n, x, y, _) = points[k * k];
+      assert_eq!(n, k * k);
+      assert!((x - k as f64).abs() < 1e-9 && y.abs() < 1e-9, "n = {}", n);
+    }
+
+    // 2 is √2 out and √2 - 1 of a turn around.
+    let (_, x, y, b) = points[2];
+    assert!(b);
+    assert!((x.hypot(y) - 2f64.sqrt()).abs() < 1e-9);
+    assert!((y.atan2(x) - 2.0 * PI * (2f64.sqrt() - 1.0)).abs() < 1e-9);
+  }
+}
@@ -0,0 +1,425 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::tile_options;
+use crate::ulam::tile::tile::Tile;
+use crate::ulam::tile::tile::MARGIN;
+use crate::ulam::tile::types::LabelMode;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::Rectangle;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Curve {
+  /// Hilbert curve over a square of side 2^k.
+  Hilbert,
+  /// Z-order over a square of side 2^k: the bits of n are split between x
+  /// and y.
+  Morton,
+  /// Peano curve over a square of side 3^k.
+  Peano,
+}
+
+impl Curve {
+  pub fn from_name(name: &str) -> Option<Curve> {
+    match name {
+      "hilbert" => Some(Curve::Hilbert),
+      "morton" => Some(Curve::Morton),
+      "peano" => Some(Curve::Peano),
+      _ => None,
+    }
+  }
+
+  fn name(&self) -> &'static str {
+    match self {
+      Curve::Hilbert => "hilbert",
+      Curve::Morton => "morton",
+      Curve::Peano => "peano",
+    }
+  }
+
+  fn base(&self) -> usize {
+    match self {
+      Curve::Hilbert | Curve::Morton => 2,
+      Curve::Peano => 3,
+    }
+  }
+
+  /// Smallest order whose square holds n cells.
+  pub fn order(&self, n: usize) -> u32 {
+    let cells = self.base() * self.base();
+    let mut order = 1;
+    while cells.checked_pow(order).is_some_and(|m| m < n) {
+      order += 1;
+    }
+
+    order
+  }
+
+  /// Cell of the i-th point of the curve of the given order.
+  pub fn point(&self, order: u32, i: usize) -> (isize, isize) {
+    match self {
+      Curve::Hilbert => hilbert(order, i),
+      Curve::Morton => morton(i),
+      Curve::Peano => peano(order, i),
+    }
+  }
+
+  /// Lowest and highest cell the first n points of the curve reach. The
+  /// base^(2l) points from a multiple of that fill an aligned square of side
+  /// base^l, so the first n split into such squares, each found from one
+  /// point.
+  pub fn bounds(&self, order: u32, n: usize) -> ((isize, isize), (isize, isize)) {
+    let cells = self.base() * self.base();
+    let (mut min, mut max) = ((isize::MAX, isize::MAX), (isize::MIN, isize::MIN));
+    let mut start = 0;
+    for level in (0..=order).rev() {
+      let size = match cells.checked_pow(level) {
+        Some(size) => size,
+        None => continue,
+      };
+
+      let side = self.base().pow(level) as isize;
+      while n - start >= size {
+        let (x, y) = self.point(order, start);
+        let corner = (x - x % side, y - y % side);
+        min = (min.0.min(corner.0), min.1.min(corner.1));
+        max = (
+          max.0.max(corner.0 + side - 1),
+          max.1.max(corner.1 + side - 1),
+        );
+        start += size;
+      }
+    }
+
+    (min, max)
+  }
+}
+
+fn hilbert(order: u32, mut i: usize) -> (isize, isize) {
+  let (mut x, mut y) = (0, 0);
+  for level in 0..order {
+    let s = 1 << level;
+    let rx = 1 & (i / 2);
+    let ry = 1 & (i ^ rx);
+
+    // The quarter is turned so its ends meet those of its neighbours.
+    if ry == 0 {
+      if rx == 1 {
+        x = s - 1 - x;
+        y = s - 1 - y;
+      }
+      std::mem::swap(&mut x, &mut y);
+    }
+
+    x += s * rx;
+    y += s * ry;
+    i /= 4;
+  }
+
+  (x as isize, y as isize)
+}
+
+fn morton(i: usize) -> (isize, isize) {
+  let (mut x, mut y) = (0, 0);
+  for bit in 0..usize::BITS / 2 {
+    x |= (i >> (2 * bit) & 1) << bit;
+    y |= (i >> (2 * bit + 1) & 1) << bit;
+  }
+
+  (x as isize, y as isize)
+}
+
+// Peano's own definition: the base 3 digits of i alternate between x and
+// y, and a digit is reflected when the earlier digits of the other
+// coordinate add up to an odd number.
+fn peano(order: u32, i: usize) -> (isize, isize) {
+  let (mut x, mut y) = (0, 0);
+  let (mut x_sum, mut y_sum) = (0, 0);
+  for level in (0..order).rev() {
+    let digits = i / 9usize.pow(level) % 9;
+    let (digit_x, digit_y) = (digits / 3, digits % 3);
+    let dx = if y_sum % 2 == 1 { 2 - digit_x } else { digit_x };
+    x_sum += digit_x;
+    let dy = if x_sum % 2 == 1 { 2 - digit_y } else { digit_y };
+    y_sum += digit_y;
+    x = 3 * x + dx;
+    y = 3 * y + dy;
+  }
+
+  (x as isize, y as isize)
+}
+
+/// Lays numbers along a space-filling curve on the square grid, so numbers
+/// close together stay close on the grid.
+pub struct SquareCurve<'a, 'b, N = usize> {
+  plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  tile: SquareCurveTile<'a, N>,
+  block: f64,
+  center: (f64, f64),
+  label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
+}
+
+impl<'a, 'b, N: Number> SquareCurve<'a, 'b, N> {
+  pub fn new(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    curve: Curve,
+  ) -> SquareCurve<'a, 'b, N> {
+    let n = gen.data_num();
+    let categories = gen.categories();
+    let heat_map = gen.value_range().map(HeatMap::new);
+    let order = curve.order(n + 1);
+
+    // The cells the curve reaches, which for a part of a square need not
+    // be a square.
+    let (min, max) = curve.bounds(order, n + 1);
+
+    let vw = (max.0 - min.0).max(max.1 - min.1) as f64 + 1.0;
+    let range = plotting_area.get_pixel_range().0;
+    let block = (range.end - range.start - MARGIN as i32) as f64 / vw;
+    SquareCurve {
+      plotting_area,
+      tile: SquareCurve::tile(gen, curve, order),
+      block,
+      center: (
+        (min.0 + max.0 + 1) as f64 / 2.0,
+        (min.1 + max.1 + 1) as f64 / 2.0,
+      ),
+      label_mode: LabelMode::None,
+      categories,
+      heat_map,
+    }
+  }
+
+  pub fn from_tp(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    curve: Curve,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::new(gen, plotting_area, curve);
+
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      heat_map_option(tile.heat_map.as_mut(), key, value)?;
+    }
+
+    Ok(tile)
+  }
+
+  fn tile(gen: Box<dyn Generator<N> + 'a>, curve: Curve, order: u32) -> SquareCurveTile<'a, N> {
+    SquareCurveTile::new(gen, curve, order)
+  }
+
+  fn normalize(&self, x: isize, y: isize) -> (f64, f64) {
+    (
+      (x as f64 - self.center.0) * self.block,
+      (y as f64 - self.center.1) * self.block,
+    )
+  }
+}
+
+impl<'a, 'b, N: Number> Tile<N> for SquareCurve<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+
+    let (n, x, y, color) = next?;
+    let coord1 = self.normalize(x, y);
+    let coord2 = self.normalize(x + 1, y + 1);
+    let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      (coord1.0, coord2.1),
+      font_size,
+      |style| {
+        self
+          .plotting_area
+          .draw(&Rectangle::new([coord1, coord2], style))
+      },
+    );
+
+    Some(r.map(|_| n))
+  }
+
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
+
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
+    }
+
+    Ok(())
+  }
+
+  fn tile_info(&self) -> std::string::String {
+    format!(
+      "tile = {}, order = {}",
+      self.tile.curve.name(),
+      self.tile.order
+    )
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
+  }
+}
+
+struct SquareCurveTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
+  curve: Curve,
+  order: u32,
+  i: usize,
+}
+
+impl<'a, N: Number> SquareCurveTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>, curve: Curve, order: u32) -> Self {
+    SquareCurveTile {
+      gen,
+      curve,
+      order,
+      i: 0,
+    }
+  }
+
+  fn next_with<T>(
+    &mut self,
+    next: impl FnOnce(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
+    if let Some((n, b)) = next(self.gen.as_mut()) {
+      let (x, y) = self.curve.point(self.order, self.i);
+      self.i += 1;
+      Some((n, x, y, b))
+    } else {
+      None
+    }
+  }
+}
+
+impl<'a, N: Number> Iterator for SquareCurveTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::ulam::generator::primes::PrimesGenerator;
+
+  fn points(curve: Curve, order: u32) -> Vec<(isize, isize)> {
+    let side = curve.base().pow(order);
+    (0..side * side).map(|i| curve.point(order, i)).collect()
+  }
+
+  #[test]
+  fn test_curves() {
+    assert_eq!(
+      points(Curve::Hilbert, 1),
+      vec![(0, 0), (0, 1), (1, 1), (1, 0)]
+    );
+    assert_eq!(
+      points(Curve::Morton, 1),
+      vec![(0, 0), (1, 0), (0, 1), (1, 1)]
+    );
+    assert_eq!(
+      points(Curve::Peano, 1),
+      vec![
+        (0, 0),
+        (0, 1),
+        (0, 2),
+        (1, 2),
+        (1, 1),
+        (1, 0),
+        (2, 0),
+        (2, 1),
+        (2, 2)
+      ]
+    );
+    assert_eq!(Curve::Hilbert.order(16), 2);
+    assert_eq!(Curve::Hilbert.order(17), 3);
+    assert_eq!(Curve::Peano.order(10), 2);
+
+    // Each curve covers its square once, and the Hilbert and Peano curves
+    // move a cell at a time.
+    for &curve in &[Curve::Hilbert, Curve::Morton, Curve::Peano] {
+      let points = points(curve, 3);
+      let side = curve.base().pow(3) as isize;
+      let mut seen = vec![false; (side * side) as usize];
+      for &(x, y) in &points {
+        seen[(y * side + x) as usize] = true;
+      }
+      assert!(seen.iter().all(|&b| b), "{:?}", curve);
+
+      if curve != Curve::Morton {
+        for pair in points.windows(2) {
+          let d = (pair[0].0 - pair[1].0).abs() + (pair[0].1 - pair[1].1).abs();
+          assert_eq!(d, 1, "{:?} {:?}", curve, pair);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_bounds() {
+    for &curve in &[Curve::Hilbert, Curve::Morton, Curve::Peano] {
+      let points = points(curve, 3);
+      let (mut min, mut max) = (points[0], points[0]);
+      for (n, &(x, y)) in points.iter().enumerate() {
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+        assert_eq!(
+          curve.bounds(3, n + 1),
+          (min, max),
+          "{:?}, n = {}",
+          curve,
+          n + 1
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_tile() {
+    let gen = PrimesGenerator::new(4, 0);
+    let mut ite = SquareCurve::tile(Box::new(gen), Curve::Hilbert, 1);
+
+    assert_eq!(ite.next(), Some((0, 0, 0, false)));
+    assert_eq!(ite.next(), Some((1, 0, 1, false)));
+    assert_eq!(ite.next(), Some((2, 1, 1, true)));
+    assert_eq!(ite.next(), Some((3, 1, 0, true)));
+  }
+}
@@ -1,184 +1,300 @@
 use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::skip_to_start;
+use crate::ulam::tile::tile::tile_options;
 use crate::ulam::tile::tile::Tile;
 use crate::ulam::tile::tile::MARGIN;
 use crate::ulam::tile::types::LabelMode;
+use crate::ulam::tile::types::Turn;
 use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
 use plotters::prelude::BitMapBackend;
 use plotters::prelude::Cartesian2d;
 use plotters::prelude::DrawingArea;
+use plotters::prelude::RGBColor;
 use plotters::prelude::Rectangle;
-use plotters::prelude::ShapeStyle;
-use plotters::prelude::Text;
-use plotters::prelude::TextStyle;
-use plotters::prelude::BLACK;
-use plotters::prelude::RED;
-use plotters::style::IntoFont;
-
-pub struct SquareSpiral<'a, 'b> {
+
+pub struct SquareSpiral<'a, 'b, N = usize> {
   plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-  tile: SquareSpiralTile<'a>,
+  tile: SquareSpiralTile<'a, N>,
   block: f64,
   label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
 }
 
-impl<'a, 'b> SquareSpiral<'a, 'b> {
+impl<'a, 'b, N: Number> SquareSpiral<'a, 'b, N> {
   pub fn new(
-    gen: Box<dyn Generator + 'a>,
+    gen: Box<dyn Generator<N> + 'a>,
     plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-  ) -> SquareSpiral<'a, 'b> {
+  ) -> SquareSpiral<'a, 'b, N> {
+    let heat_map = gen.value_range().map(HeatMap::new);
+    Self::with_heat_map(gen, plotting_area, heat_map)
+  }
+
+  /// Square spiral coloured from `heat_map`, or by category without one.
+  /// The gaussian lattice gives `None`, as its cells are coloured by their
+  /// lattice points and there are no values to scan a range from.
+  pub(crate) fn with_heat_map(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    heat_map: Option<HeatMap>,
+  ) -> SquareSpiral<'a, 'b, N> {
     let n = gen.data_num();
-    let vw = (n as f64).sqrt().ceil();
-    let range = plotting_area.get_pixel_range().0;
-    let block = (range.end - range.start - MARGIN as i32) as f64 / vw;
+    let categories = gen.categories();
     SquareSpiral {
       plotting_area,
       tile: SquareSpiral::tile(gen),
-      block,
+      block: Self::block(plotting_area, n),
       label_mode: LabelMode::None,
+      categories,
+      heat_map,
     }
   }
 
   pub fn from_tp(
     tp: &str,
-    gen: Box<dyn Generator + 'a>,
+    gen: Box<dyn Generator<N> + 'a>,
     plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
   ) -> Result<Self, Box<dyn std::error::Error>> {
-    let mut tile = Self::new(gen, plotting_area);
+    let heat_map = gen.value_range().map(HeatMap::new);
+    Self::from_tp_with_heat_map(tp, gen, plotting_area, heat_map)
+  }
 
-    let mut tp = tp.split(":");
-    let label_mode = tp.next();
+  /// `with_heat_map` with the options of `tp` applied: the four `dir`s
+  /// from `up` to `right`, `turn` and `start`.
+  pub(crate) fn from_tp_with_heat_map(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    heat_map: Option<HeatMap>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::with_heat_map(gen, plotting_area, heat_map);
 
-    let label_mode: usize = if let Some(from) = label_mode {
-      from.parse()?
-    } else {
-      0
-    };
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      match key {
+        "turn" => tile.tile.turn = value.parse()?,
+        "dir" => {
+          tile.tile.dir = match value {
+            "up" => 0,
+            "left" => 1,
+            "down" => 2,
+            "right" => 3,
+            _ => return Err(format!("Unknown direction({})", value).into()),
+          }
+        }
+        "start" => tile.tile.start = Some(N::parse(value)?),
+        _ => heat_map_option(tile.heat_map.as_mut(), key, value)?,
+      }
+    }
 
-    tile.label_mode = LabelMode::from(label_mode);
     Ok(tile)
   }
 
-  fn tile(gen: Box<dyn Generator + 'a>) -> SquareSpiralTile<'a> {
+  fn tile(gen: Box<dyn Generator<N> + 'a>) -> SquareSpiralTile<'a, N> {
     SquareSpiralTile::new(gen)
   }
 
+  // Size of a cell when the spiral runs n cells out from the centre.
+  fn block(
+    plotting_area: &DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    n: usize,
+  ) -> f64 {
+    let vw = (n as f64).sqrt().ceil();
+    let range = plotting_area.get_pixel_range().0;
+    (range.end - range.start - MARGIN as i32) as f64 / vw
+  }
+
   fn normalize(&self, x: isize, y: isize) -> (f64, f64) {
     (
       ((x as f64 - 1.0) * self.block),
       ((y as f64 - 1.0) * self.block),
     )
   }
+
+  /// Draws the next number like `draw_next`, in the colour `color` gives its
+  /// lattice point rather than by the generator.
+  pub(crate) fn draw_next_by(
+    &mut self,
+    color: impl Fn(isize, isize) -> Option<RGBColor>,
+  ) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let next = self.tile.next_with(|gen| gen.next());
+    self.draw(next.map(|(n, x, y, _)| (n, x, y, color(x, y))))
+  }
+
+  // Draws the cell of a number, given with its lattice point and colour.
+  fn draw(
+    &mut self,
+    next: Option<(N, isize, isize, Option<RGBColor>)>,
+  ) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    if let Some(last) = self.tile.last.take() {
+      self.block = Self::block(self.plotting_area, last);
+    }
+
+    let (n, x, y, color) = next?;
+    let coord1 = self.normalize(x, y);
+    let coord2 = self.normalize(x + 1, y + 1);
+    let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      (coord1.0, coord2.1),
+      font_size,
+      |style| {
+        self
+          .plotting_area
+          .draw(&Rectangle::new([coord1, coord2], style))
+      },
+    );
+
+    Some(r.map(|_| n))
+  }
 }
 
-impl<'a, 'b> Tile for SquareSpiral<'a, 'b> {
-  fn draw_next(&mut self) -> Option<Result<usize, Box<dyn std::error::Error>>> {
-    if let Some((n, x, y, b)) = self.tile.next() {
-      let coord1 = self.normalize(x, y);
-      let coord2 = self.normalize(x + 1, y + 1);
-
-      if b {
-        let mut r = self.plotting_area.draw(&Rectangle::new(
-          [coord1, coord2],
-          Into::<ShapeStyle>::into(&RED).filled(),
-        ));
-
-        if let Err(err) = r {
-          return Some(Err(Box::new(err)));
-        };
-
-        if self.label_mode == LabelMode::All || self.label_mode == LabelMode::OnlyPositive {
-          let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
-          let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
-          r = self
-            .plotting_area
-            .draw(&Text::new(n.to_string(), (coord1.0, coord2.1), &style));
-        }
+impl<'a, 'b, N: Number> Tile<N> for SquareSpiral<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+    self.draw(next)
+  }
 
-        return match r {
-          Ok(_) => Some(Ok(n)),
-          Err(err) => Some(Err(Box::new(err))),
-        };
-      } else {
-        if self.label_mode == LabelMode::All || self.label_mode == LabelMode::OnlyNegative {
-          let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
-          let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
-          let r = self
-            .plotting_area
-            .draw(&Text::new(n.to_string(), (coord1.0, coord2.1), &style));
-
-          return match r {
-            Ok(_) => Some(Ok(n)),
-            Err(err) => Some(Err(Box::new(err))),
-          };
-        }
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
 
-        Some(Ok(n))
-      }
-    } else {
-      return None;
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
     }
+
+    Ok(())
   }
 
   fn tile_info(&self) -> std::string::String {
-    format!("tile = spiral4")
+    match &self.tile.start {
+      Some(start) => format!("tile = spiral4, start = {}", start),
+      None => "tile = spiral4".to_string(),
+    }
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
   }
 }
 
-struct SquareSpiralTile<'a> {
-  gen: Box<dyn Generator + 'a>,
+struct SquareSpiralTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
   transit_info: (usize, isize, isize),
-  prev: (usize, isize, isize, bool),
+  prev: Option<(isize, isize)>,
+  dir: usize,
+  turn: Turn,
+  start: Option<N>,
+  last: Option<usize>,
 }
 
-impl<'a> SquareSpiralTile<'a> {
-  pub fn new(gen: Box<dyn Generator + 'a>) -> Self {
+impl<'a, N: Number> SquareSpiralTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>) -> Self {
     SquareSpiralTile {
       gen,
-      transit_info: (3, 2, 1), // (dir, rest, step)
-      prev: (0, -1, 0, false),
+      transit_info: (3, 1, 1), // (dir, rest, step)
+      prev: None,
+      dir: 3,
+      turn: Turn::Counterclockwise,
+      start: None,
+      last: None,
     }
   }
-}
 
-impl<'a> Iterator for SquareSpiralTile<'a> {
-  type Item = (usize, isize, isize, bool);
+  fn next_with<T>(
+    &mut self,
+    mut next: impl FnMut(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
+    let mut first = next(self.gen.as_mut())?;
 
-  fn next(&mut self) -> std::option::Option<Self::Item> {
-    // 0: up, 1: left, 2: down, 3: right
+    if let (None, Some(start)) = (self.prev, self.start.clone()) {
+      let (at_start, offset, last) = skip_to_start(self.gen.as_mut(), &mut next, first, start)?;
+      first = at_start;
+      for _ in 0..offset {
+        self.step();
+      }
+      self.last = Some(last);
+    }
 
-    if let Some((n, b)) = self.gen.next() {
-      let (dir, mut rest, step) = self.transit_info;
-      let (_, x, y, _) = self.prev;
-      let ret = if dir == 0 {
-        (n, x, y + 1, b)
-      } else if dir == 1 {
-        (n, x - 1, y, b)
-      } else if dir == 2 {
-        (n, x, y - 1, b)
-      } else {
-        (n, x + 1, y, b)
-      };
+    let (n, b) = first;
+    let (x, y) = self.step();
+    Some((n, x, y, b))
+  }
 
-      self.prev = ret;
-      rest -= 1;
-      if rest == 0 {
-        if dir == 0 {
-          self.transit_info = (1, step + 1, step + 1);
-        } else if dir == 1 {
-          self.transit_info = (2, step, step);
-        } else if dir == 2 {
-          self.transit_info = (3, step + 1, step + 1);
-        } else {
-          self.transit_info = (0, step, step);
-        }
-      } else {
-        self.transit_info = (dir, rest, step);
+  fn step(&mut self) -> (isize, isize) {
+    // 0: up, 1: left, 2: down, 3: right
+
+    let (x, y) = match self.prev {
+      Some(prev) => prev,
+      None => {
+        // The centre, then the first leg in the initial direction.
+        self.transit_info = (self.dir, 1, 1);
+        self.prev = Some((0, 0));
+        return (0, 0);
       }
+    };
 
-      return Some(ret);
+    let (dir, mut rest, step) = self.transit_info;
+    let ret = if dir == 0 {
+      (x, y + 1)
+    } else if dir == 1 {
+      (x - 1, y)
+    } else if dir == 2 {
+      (x, y - 1)
     } else {
-      return None;
+      (x + 1, y)
+    };
+
+    self.prev = Some(ret);
+    rest -= 1;
+    if rest == 0 {
+      // Legs grow by one after every second turn.
+      let grow = (dir + 4 - self.dir) % 2;
+      let dir = match self.turn {
+        Turn::Counterclockwise => (dir + 1) % 4,
+        Turn::Clockwise => (dir + 3) % 4,
+      };
+      self.transit_info = (dir, step + grow as isize, step + grow as isize);
+    } else {
+      self.transit_info = (dir, rest, step);
     }
+
+    ret
+  }
+}
+
+impl<'a, N: Number> Iterator for SquareSpiralTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
   }
 }
 
@@ -206,4 +322,36 @@ mod tests {
     assert_eq!(ite.next(), Some((10, 2, 0, false)));
     assert_eq!(ite.next(), None);
   }
+
+  #[test]
+  fn test_tile_options() {
+    let gen = PrimesGenerator::new(6, 0);
+    let mut ite = SquareSpiral::tile(Box::new(gen));
+    ite.dir = 0;
+    ite.turn = Turn::Clockwise;
+
+    assert_eq!(ite.next(), Some((0, 0, 0, false)));
+    assert_eq!(ite.next(), Some((1, 0, 1, false)));
+    assert_eq!(ite.next(), Some((2, 1, 1, true)));
+    assert_eq!(ite.next(), Some((3, 1, 0, true)));
+    assert_eq!(ite.next(), Some((4, 1, -1, false)));
+    assert_eq!(ite.next(), Some((5, 0, -1, true)));
+    assert_eq!(ite.next(), Some((6, -1, -1, false)));
+
+    // Numbers below the centre are left out.
+    let gen = PrimesGenerator::new(5, 1);
+    let mut ite = SquareSpiral::tile(Box::new(gen));
+    ite.start = Some(3);
+    assert_eq!(ite.next(), Some((3, 0, 0, true)));
+    assert_eq!(ite.last, Some(2));
+    assert_eq!(ite.next(), Some((4, 1, 0, false)));
+
+    // Numbers above it start further out.
+    let gen = PrimesGenerator::new(5, 2);
+    let mut ite = SquareSpiral::tile(Box::new(gen));
+    ite.start = Some(0);
+    assert_eq!(ite.next(), Some((2, 1, 1, true)));
+    assert_eq!(ite.last, Some(5));
+    assert_eq!(ite.next(), Some((3, 0, 1, true)));
+  }
 }
@@ -1,32 +1,38 @@
 use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::tile_options;
 use crate::ulam::tile::tile::Tile;
 use crate::ulam::tile::tile::MARGIN;
 use crate::ulam::tile::types::LabelMode;
 use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
 use plotters::prelude::BitMapBackend;
 use plotters::prelude::Cartesian2d;
 use plotters::prelude::DrawingArea;
 use plotters::prelude::Rectangle;
-use plotters::prelude::ShapeStyle;
-use plotters::prelude::Text;
-use plotters::prelude::TextStyle;
-use plotters::prelude::BLACK;
-use plotters::prelude::RED;
-use plotters::style::IntoFont;
-
-pub struct SquareZigzag<'a, 'b> {
+
+pub struct SquareZigzag<'a, 'b, N = usize> {
   plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-  tile: SquareZigzagTile<'a>,
+  tile: SquareZigzagTile<'a, N>,
   block: f64,
   label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
 }
 
-impl<'a, 'b> SquareZigzag<'a, 'b> {
+impl<'a, 'b, N: Number> SquareZigzag<'a, 'b, N> {
   pub fn new(
-    gen: Box<dyn Generator + 'a>,
+    gen: Box<dyn Generator<N> + 'a>,
     plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-  ) -> SquareZigzag<'a, 'b> {
+  ) -> SquareZigzag<'a, 'b, N> {
     let n = gen.data_num();
+    let categories = gen.categories();
+    let heat_map = gen.value_range().map(HeatMap::new);
     let vw = (n as f64).sqrt().ceil();
     let range = plotting_area.get_pixel_range().0;
     let block = (range.end - range.start - MARGIN as i32) as f64 / vw;
@@ -35,26 +41,25 @@ impl<'a, 'b> SquareZigzag<'a, 'b> {
       tile: SquareZigzag::tile(gen),
       block,
       label_mode: LabelMode::None,
+      categories,
+      heat_map,
     }
   }
 
   pub fn from_tp(
     tp: &str,
-    gen: Box<dyn Generator + 'a>,
+    gen: Box<dyn Generator<N> + 'a>,
     plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
   ) -> Result<Self, Box<dyn std::error::Error>> {
     let mut tile = Self::new(gen, plotting_area);
 
-    let mut tp = tp.split(":");
-    let label_mode = tp.next();
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
 
-    let label_mode: usize = if let Some(from) = label_mode {
-      from.parse()?
-    } else {
-      0
-    };
+    for (key, value) in options {
+      heat_map_option(tile.heat_map.as_mut(), key, value)?;
+    }
 
-    tile.label_mode = LabelMode::from(label_mode);
     Ok(tile)
   }
 
@@ -62,95 +67,100 @@ impl<'a, 'b> SquareZigzag<'a, 'b> {
     let o1 = self.plotting_area.get_x_range();
     let o2 = self.plotting_area.get_y_range();
     (
-      (x as f64 * self.block + o1.start as f64),
-      (y as f64 * self.block + o2.start as f64),
+      (x as f64 * self.block + o1.start),
+      (y as f64 * self.block + o2.start),
     )
   }
 
-  fn tile(gen: Box<dyn Generator + 'a>) -> SquareZigzagTile<'a> {
+  fn tile(gen: Box<dyn Generator<N> + 'a>) -> SquareZigzagTile<'a, N> {
     SquareZigzagTile::new(gen)
   }
 }
 
-impl<'a, 'b> Tile for SquareZigzag<'a, 'b> {
-  fn draw_next(&mut self) -> Option<Result<usize, Box<dyn std::error::Error>>> {
-    if let Some((n, x, y, b)) = self.tile.next() {
-      let coord1 = self.normalize(x, y);
-      let coord2 = self.normalize(x + 1, y + 1);
-
-      if b {
-        let mut r = self.plotting_area.draw(&Rectangle::new(
-          [coord1, coord2],
-          Into::<ShapeStyle>::into(&RED).filled(),
-        ));
-
-        if let Err(err) = r {
-          return Some(Err(Box::new(err)));
-        };
-
-        if self.label_mode == LabelMode::All || self.label_mode == LabelMode::OnlyPositive {
-          let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
-          let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
-          r = self
-            .plotting_area
-            .draw(&Text::new(n.to_string(), (coord1.0, coord2.1), &style));
-        }
+impl<'a, 'b, N: Number> Tile<N> for SquareZigzag<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+
+    let (n, x, y, color) = next?;
+    let coord1 = self.normalize(x, y);
+    let coord2 = self.normalize(x + 1, y + 1);
+    let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      (coord1.0, coord2.1),
+      font_size,
+      |style| {
+        self
+          .plotting_area
+          .draw(&Rectangle::new([coord1, coord2], style))
+      },
+    );
+
+    Some(r.map(|_| n))
+  }
 
-        return match r {
-          Ok(_) => Some(Ok(n)),
-          Err(err) => Some(Err(Box::new(err))),
-        };
-      } else {
-        if self.label_mode == LabelMode::All || self.label_mode == LabelMode::OnlyNegative {
-          let font_size = (self.block / 2.0).min(MARGIN as f64).max(8.0);
-          let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
-          let r = self
-            .plotting_area
-            .draw(&Text::new(n.to_string(), (coord1.0, coord2.1), &style));
-
-          return match r {
-            Ok(_) => Some(Ok(n)),
-            Err(err) => Some(Err(Box::new(err))),
-          };
-        }
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
 
-        Some(Ok(n))
-      }
-    } else {
-      return None;
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
     }
+
+    Ok(())
   }
 
   fn tile_info(&self) -> std::string::String {
-    format!("tile = zigzag4")
+    "tile = zigzag4".to_string()
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
   }
 }
 
-struct SquareZigzagTile<'a> {
-  gen: Box<dyn Generator + 'a>,
+struct SquareZigzagTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
   transit_info: (usize, isize, isize),
-  prev: (usize, isize, isize, bool),
+  prev: (isize, isize),
 }
 
-impl<'a> SquareZigzagTile<'a> {
-  pub fn new(gen: Box<dyn Generator + 'a>) -> Self {
+impl<'a, N: Number> SquareZigzagTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>) -> Self {
     SquareZigzagTile {
       gen,
       transit_info: (0, 2, 0), // (dir, rest, cycle)
-      prev: (0, -1, 0, false),
+      prev: (-1, 0),
     }
   }
-}
-
-impl<'a> Iterator for SquareZigzagTile<'a> {
-  type Item = (usize, isize, isize, bool);
 
-  fn next(&mut self) -> std::option::Option<Self::Item> {
+  fn next_with<T>(
+    &mut self,
+    next: impl FnOnce(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
     // 0: right, 1: up, 2: left, 3: up2, 4: right2, 5: down
 
-    if let Some((n, b)) = self.gen.next() {
+    if let Some((n, b)) = next(self.gen.as_mut()) {
       let (dir, mut rest, cycle) = self.transit_info;
-      let (_, x, y, _) = self.prev;
+      let (x, y) = self.prev;
       let ret = if dir == 0 || dir == 4 {
         (n, x + 1, y, b)
       } else if dir == 1 || dir == 3 {
@@ -168,7 +178,7 @@ impl<'a> Iterator for SquareZigzagTile<'a> {
       // 4: right -> 2n
       // 5: down -> 2n
 
-      self.prev = ret;
+      self.prev = (ret.1, ret.2);
       rest -= 1;
       if rest == 0 {
         if dir == 0 {
@@ -188,13 +198,21 @@ impl<'a> Iterator for SquareZigzagTile<'a> {
         self.transit_info = (dir, rest, cycle);
       }
 
-      return Some(ret);
+      Some(ret)
     } else {
-      return None;
+      None
     }
   }
 }
 
+impl<'a, N: Number> Iterator for SquareZigzagTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
+  }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1,6 +1,173 @@
-pub trait Tile {
-  fn draw_next(&mut self) -> Option<Result<usize, Box<dyn std::error::Error>>>;
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::category_color;
+use crate::ulam::tile::types::LabelMode;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::RGBColor;
+use plotters::prelude::ShapeStyle;
+use plotters::prelude::Text;
+use plotters::prelude::TextStyle;
+use plotters::prelude::BLACK;
+use plotters::style::IntoFont;
+
+pub trait Tile<N = usize> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>>;
   fn tile_info(&self) -> String;
+
+  /// Info of the generator being drawn. Generators that count what they
+  /// yield report the numbers drawn so far.
+  fn generator_info(&self) -> String;
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>>;
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+pub const MARGIN: usize = 80;
+
+/// Label mode and `key=value` options of a `tp`.
+pub(crate) type TileOptions<'a> = (LabelMode, Vec<(&'a str, &'a str)>);
+
+/// Splits `tp`, `label_mode:key=value:...`, into the label mode and the
+/// options.
+pub(crate) fn tile_options(tp: &str) -> Result<TileOptions<'_>, Box<dyn std::error::Error>> {
+  let mut tp = tp.split(":");
+  let label_mode = tp.next();
+
+  let label_mode: usize = if let Some(from) = label_mode {
+    from.parse()?
+  } else {
+    0
+  };
+
+  if label_mode > 3 {
+    return Err(format!("Unknown label mode({})", label_mode).into());
+  }
+
+  let options = tp
+    .map(|option| {
+      option
+        .split_once('=')
+        .ok_or_else(|| format!("Tile option must be key=value({})", option))
+    })
+    .collect::<Result<_, _>>()?;
+
+  Ok((LabelMode::from(label_mode), options))
+}
+
+/// Applies a colour map option, the options every tile takes. Any other key,
+/// or a colour map option for a tile not drawn as a heat map, is an error.
+pub(crate) fn heat_map_option(
+  heat_map: Option<&mut HeatMap>,
+  key: &str,
+  value: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+  match (heat_map, key) {
+    (Some(heat_map), "cmap") => heat_map.color_map = value.parse()?,
+    (Some(heat_map), "scale") => heat_map.scale = value.parse()?,
+    _ => return Err(format!("Unknown tile option({})", key).into()),
+  }
+
+  Ok(())
 }
 
-pub const MARGIN: usize = 80;
\ No newline at end of file
+/// Next number of `gen` with the colour it is drawn in, or `None` to leave it
+/// blank: from the heat map when there is one, otherwise by category.
+pub(crate) fn next_color<N>(
+  gen: &mut dyn Generator<N>,
+  heat_map: Option<&HeatMap>,
+  categories: usize,
+) -> Option<(N, Option<RGBColor>)> {
+  if let Some(heat_map) = heat_map {
+    gen
+      .next_value()
+      .map(|(n, v)| (n, v.map(|v| heat_map.color(v))))
+  } else {
+    gen
+      .next_category()
+      .map(|(n, c)| (n, (c != 0).then(|| category_color(c, categories))))
+  }
+}
+
+/// Moves a spiral centred on `start` past the numbers below it, which have no
+/// cell. `first` is the number already read from `gen`, and `next` reads the
+/// ones after it. Gives the first number at or above `start`, the steps from
+/// the centre to its cell, and the steps to the cell of the last number.
+pub(crate) fn skip_to_start<N: Number, T>(
+  gen: &mut dyn Generator<N>,
+  mut next: impl FnMut(&mut dyn Generator<N>) -> Option<(N, T)>,
+  first: (N, T),
+  start: N,
+) -> Option<((N, T), usize, usize)> {
+  let (mut n, mut b) = first;
+  let mut skipped = 0;
+  while n < start {
+    (n, b) = next(gen)?;
+    skipped += 1;
+  }
+
+  let offset = (n.clone() - start).to_usize()?;
+  let last = offset + gen.data_num() - skipped;
+  Some(((n, b), offset, last))
+}
+
+/// Draws the cell of n: `fill` draws its shape in the given style when n has
+/// a colour, and n is written at `label` when `label_mode` asks for it.
+pub(crate) fn draw_cell<N: Number, E: std::error::Error + 'static>(
+  plotting_area: &DrawingArea<BitMapBackend, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  n: &N,
+  color: Option<RGBColor>,
+  label_mode: LabelMode,
+  label: (f64, f64),
+  font_size: f64,
+  fill: impl FnOnce(ShapeStyle) -> Result<(), E>,
+) -> Result<(), Box<dyn std::error::Error>> {
+  let labelled = if let Some(color) = color {
+    fill(Into::<ShapeStyle>::into(&color).filled())?;
+    label_mode == LabelMode::All || label_mode == LabelMode::OnlyPositive
+  } else {
+    label_mode == LabelMode::All || label_mode == LabelMode::OnlyNegative
+  };
+
+  if labelled {
+    let style = TextStyle::from(("sans-serif", font_size).into_font()).color(&BLACK);
+    plotting_area.draw(&Text::new(n.to_string(), label, &style))?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ulam::tile::colormap::ColorMap;
+
+  #[test]
+  fn test_tile_options() {
+    let (label_mode, options) = tile_options("1:turn=cw:cmap=magma").unwrap();
+    assert!(label_mode == LabelMode::OnlyPositive);
+    assert_eq!(options, vec![("turn", "cw"), ("cmap", "magma")]);
+    assert!(tile_options("").is_err());
+    assert!(tile_options("4").is_err());
+    assert!(tile_options("0:cmap").is_err());
+  }
+
+  #[test]
+  fn test_heat_map_option() {
+    let mut heat_map = HeatMap::new((0.0, 1.0));
+    heat_map_option(Some(&mut heat_map), "cmap", "magma").unwrap();
+    assert_eq!(heat_map.color_map, ColorMap::Magma);
+    assert!(heat_map_option(Some(&mut heat_map), "cmpa", "viridis").is_err());
+    assert!(heat_map_option(None, "cmap", "viridis").is_err());
+    assert!(heat_map_option(None, "cmpa", "viridis").is_err());
+  }
+}
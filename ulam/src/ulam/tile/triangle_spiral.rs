@@ -0,0 +1,292 @@
+use crate::ulam::generator::generator::Generator;
+use crate::ulam::generator::number::Number;
+use crate::ulam::tile::colormap::HeatMap;
+use crate::ulam::tile::palette::draw_legend;
+use crate::ulam::tile::tile::draw_cell;
+use crate::ulam::tile::tile::heat_map_option;
+use crate::ulam::tile::tile::next_color;
+use crate::ulam::tile::tile::tile_options;
+use crate::ulam::tile::tile::Tile;
+use crate::ulam::tile::tile::MARGIN;
+use crate::ulam::tile::types::LabelMode;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::BitMapBackend;
+use plotters::prelude::Cartesian2d;
+use plotters::prelude::DrawingArea;
+use plotters::prelude::Polygon;
+use std::collections::VecDeque;
+
+/// Height of a triangle of side 1.
+pub(crate) const ROW_HEIGHT: f64 = 0.8660254037844386;
+
+/// Corners of the triangle (x, y) of side 1 on the triangular lattice, in
+/// row y. It points up when x + y is even, and shares its left and right
+/// edges with (x - 1, y) and (x + 1, y).
+pub(crate) fn triangle(x: isize, y: isize) -> [(f64, f64); 3] {
+  let up = (x + y).rem_euclid(2) == 0;
+  let (x, bottom, top) = (
+    x as f64 / 2.0,
+    y as f64 * ROW_HEIGHT,
+    (y + 1) as f64 * ROW_HEIGHT,
+  );
+  if up {
+    [(x - 0.5, bottom), (x + 0.5, bottom), (x, top)]
+  } else {
+    [(x - 0.5, top), (x + 0.5, top), (x, bottom)]
+  }
+}
+
+/// Spiral of triangles that winds counter-clockwise in rings, each ring
+/// making a triangle three rows wider than the last. Consecutive triangles
+/// share an edge, or a corner where the spiral turns around a corner.
+pub struct TriangleSpiral<'a, 'b, N = usize> {
+  plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  tile: TriangleSpiralTile<'a, N>,
+  block: f64,
+  center: f64,
+  label_mode: LabelMode,
+  categories: Vec<String>,
+  heat_map: Option<HeatMap>,
+}
+
+impl<'a, 'b, N: Number> TriangleSpiral<'a, 'b, N> {
+  pub fn new(
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> TriangleSpiral<'a, 'b, N> {
+    let n = gen.data_num();
+    let categories = gen.categories();
+    let heat_map = gen.value_range().map(HeatMap::new);
+
+    // Rings 0 to k fill a triangle of side 3k + 1.
+    let mut rings = 0;
+    while (3 * rings + 1) * (3 * rings + 1) < n + 1 {
+      rings += 1;
+    }
+
+    let vw = (3 * rings + 1) as f64;
+    let range = plotting_area.get_pixel_range().0;
+    let block = (range.end - range.start - MARGIN as i32) as f64 / vw;
+    TriangleSpiral {
+      plotting_area,
+      tile: TriangleSpiral::tile(gen),
+      block,
+      // The triangle spans rows -k to 2k.
+      center: (rings + 1) as f64 / 2.0 * ROW_HEIGHT,
+      label_mode: LabelMode::None,
+      categories,
+      heat_map,
+    }
+  }
+
+  pub fn from_tp(
+    tp: &str,
+    gen: Box<dyn Generator<N> + 'a>,
+    plotting_area: &'a DrawingArea<BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+  ) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut tile = Self::new(gen, plotting_area);
+
+    let (label_mode, options) = tile_options(tp)?;
+    tile.label_mode = label_mode;
+
+    for (key, value) in options {
+      heat_map_option(tile.heat_map.as_mut(), key, value)?;
+    }
+
+    Ok(tile)
+  }
+
+  fn tile(gen: Box<dyn Generator<N> + 'a>) -> TriangleSpiralTile<'a, N> {
+    TriangleSpiralTile::new(gen)
+  }
+
+  fn normalize(&self, (x, y): (f64, f64)) -> (f64, f64) {
+    (x * self.block, (y - self.center) * self.block)
+  }
+}
+
+impl<'a, 'b, N: Number> Tile<N> for TriangleSpiral<'a, 'b, N> {
+  fn draw_next(&mut self) -> Option<Result<N, Box<dyn std::error::Error>>> {
+    let heat_map = self.heat_map.as_ref();
+    let categories = self.categories.len();
+    let next = self
+      .tile
+      .next_with(|gen| next_color(gen, heat_map, categories));
+
+    let (n, x, y, color) = next?;
+    let points: Vec<_> = triangle(x, y).iter().map(|&p| self.normalize(p)).collect();
+    let label = self.normalize((x as f64 / 2.0 - 0.25, (y as f64 + 0.6) * ROW_HEIGHT));
+    // Triangles are half a block wide on average.
+    let font_size = (self.block / 4.0).min(MARGIN as f64).max(8.0);
+    let r = draw_cell(
+      self.plotting_area,
+      &n,
+      color,
+      self.label_mode,
+      label,
+      font_size,
+      |style| self.plotting_area.draw(&Polygon::new(points, style)),
+    );
+
+    Some(r.map(|_| n))
+  }
+
+  fn draw_legend(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if self.heat_map.is_some() {
+      return Ok(());
+    }
+
+    draw_legend(area, &self.categories)
+  }
+
+  fn draw_color_bar(
+    &self,
+    area: &DrawingArea<BitMapBackend, Shift>,
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(heat_map) = &self.heat_map {
+      heat_map.draw_color_bar(area)?;
+    }
+
+    Ok(())
+  }
+
+  fn tile_info(&self) -> std::string::String {
+    "tile = spiral3".to_string()
+  }
+
+  fn generator_info(&self) -> std::string::String {
+    self.tile.gen.generator_info()
+  }
+}
+
+struct TriangleSpiralTile<'a, N> {
+  gen: Box<dyn Generator<N> + 'a>,
+  ring: isize,
+  cells: VecDeque<(isize, isize)>,
+}
+
+impl<'a, N: Number> TriangleSpiralTile<'a, N> {
+  pub fn new(gen: Box<dyn Generator<N> + 'a>) -> Self {
+    TriangleSpiralTile {
+      gen,
+      ring: 0,
+      cells: VecDeque::from(vec![(0, 0)]),
+    }
+  }
+
+  // Ring k adds the triangles of rows -k to 2k that the triangle of ring
+  // k - 1 leaves out: a strip of two per row up the right side, the apex,
+  // a strip down the left side and the whole bottom row.
+  fn push_ring(&mut self) {
+    self.ring += 1;
+    let k = self.ring;
+
+    // Row -k + 1 is taken inner triangle first, to follow on from the
+    // bottom right corner of the last ring.
+    self.cells.push_back((3 * k - 2, -k + 1));
+    self.cells.push_back((3 * k - 1, -k + 1));
+    for y in -k + 2..2 * k - 1 {
+      self.cells.push_back((2 * k - y, y));
+      self.cells.push_back((2 * k - 1 - y, y));
+    }
+
+    self.cells.push_back((1, 2 * k - 1));
+    self.cells.push_back((0, 2 * k - 1));
+    self.cells.push_back((0, 2 * k));
+    self.cells.push_back((-1, 2 * k - 1));
+
+    for y in (-k + 1..2 * k - 1).rev() {
+      self.cells.push_back((-(2 * k - 1 - y), y));
+      self.cells.push_back((-(2 * k - y), y));
+    }
+
+    for x in -3 * k..=3 * k {
+      self.cells.push_back((x, -k));
+    }
+  }
+
+  fn next_with<T>(
+    &mut self,
+    next: impl FnOnce(&mut dyn Generator<N>) -> Option<(N, T)>,
+  ) -> Option<(N, isize, isize, T)> {
+    if let Some((n, b)) = next(self.gen.as_mut()) {
+      if self.cells.is_empty() {
+        self.push_ring();
+      }
+
+      let (x, y) = self.cells.pop_front()?;
+      Some((n, x, y, b))
+    } else {
+      None
+    }
+  }
+}
+
+impl<'a, N: Number> Iterator for TriangleSpiralTile<'a, N> {
+  type Item = (N, isize, isize, bool);
+
+  fn next(&mut self) -> std::option::Option<Self::Item> {
+    self.next_with(|gen| gen.next())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+  use crate::ulam::generator::primes::PrimesGenerator;
+  use std::collections::HashSet;
+
+  #[test]
+  fn test_tile() {
+    let gen = PrimesGenerator::new(17, 0);
+    let mut ite = TriangleSpiral::tile(Box::new(gen));
+
+    assert_eq!(ite.next(), Some((0, 0, 0, false)));
+    assert_eq!(ite.next(), Some((1, 1, 0, false)));
+    assert_eq!(ite.next(), Some((2, 2, 0, true)));
+    assert_eq!(ite.next(), Some((3, 1, 1, true)));
+    assert_eq!(ite.next(), Some((4, 0, 1, false)));
+    assert_eq!(ite.next(), Some((5, 0, 2, true)));
+    assert_eq!(ite.next(), Some((6, -1, 1, false)));
+    assert_eq!(ite.next(), Some((7, -1, 0, true)));
+    assert_eq!(ite.next(), Some((8, -2, 0, false)));
+    assert_eq!(ite.next(), Some((9, -3, -1, false)));
+    assert_eq!(ite.nth(5), Some((15, 3, -1, false)));
+    assert_eq!(ite.next(), Some((16, 4, -1, false)));
+    assert_eq!(ite.next(), Some((17, 5, -1, true)));
+  }
+
+  #[test]
+  fn test_rings() {
+    let gen = PrimesGenerator::new(99, 0);
+    let cells: Vec<_> = TriangleSpiral::tile(Box::new(gen))
+      .map(|(_, x, y, _)| (x, y))
+      .collect();
+
+    // Rings 0 to 2 fill the triangle of side 7 with its apex at (0, 4).
+    let side7: HashSet<_> = (-2..=4)
+      .flat_map(|y: isize| (-(4 - y)..=4 - y).map(move |x| (x, y)))
+      .collect();
+    assert_eq!(cells[..49].iter().copied().collect::<HashSet<_>>(), side7);
+
+    // Consecutive triangles share at least a corner.
+    for pair in cells.windows(2) {
+      let [a, b] = [
+        triangle(pair[0].0, pair[0].1),
+        triangle(pair[1].0, pair[1].1),
+      ];
+      assert!(
+        a.iter().any(|p| b
+          .iter()
+          .any(|q| (p.0 - q.0).abs() < 1e-9 && (p.1 - q.1).abs() < 1e-9)),
+        "{:?}",
+        pair
+      );
+    }
+  }
+}
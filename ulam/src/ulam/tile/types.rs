@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 #[derive(Eq, PartialEq, Copy, Clone)]
 pub enum LabelMode {
   None = 0,
@@ -17,3 +19,22 @@ impl From<usize> for LabelMode {
     }
   }
 }
+
+/// Way a spiral turns as it winds outwards.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Turn {
+  Counterclockwise,
+  Clockwise,
+}
+
+impl FromStr for Turn {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "ccw" => Ok(Turn::Counterclockwise),
+      "cw" => Ok(Turn::Clockwise),
+      _ => Err(format!("Unknown turn({})", s)),
+    }
+  }
+}